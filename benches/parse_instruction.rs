@@ -0,0 +1,48 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use solana_pumpfun::parser::parse_instruction;
+use std::hint::black_box;
+
+fn fixture(log: &str) -> Vec<String> {
+    log.lines().map(str::to_string).collect()
+}
+
+// a vote-free mix roughly like the live feed: mostly trades, some creates,
+// router CPIs and unrelated programs
+fn batch() -> Vec<Vec<String>> {
+    let create = fixture(include_str!("../tests/fixtures/direct_create.log"));
+    let buy = fixture(include_str!("../tests/fixtures/direct_buy.log"));
+    let routed = fixture(include_str!("../tests/fixtures/jupiter_routed_swap.log"));
+    let noise = fixture(
+        "Program ComputeBudget111111111111111111111111111111 invoke [1]\n\
+         Program ComputeBudget111111111111111111111111111111 success\n\
+         Program 11111111111111111111111111111111 invoke [1]\n\
+         Program 11111111111111111111111111111111 success",
+    );
+
+    (0..1_000)
+        .map(|i| match i % 10 {
+            0 => create.clone(),
+            1 | 2 => routed.clone(),
+            3 => noise.clone(),
+            _ => buy.clone(),
+        })
+        .collect()
+}
+
+fn bench_parse_instruction(c: &mut Criterion) {
+    let batch = batch();
+
+    let mut group = c.benchmark_group("parse_instruction");
+    group.throughput(Throughput::Elements(batch.len() as u64));
+    group.bench_function("mixed_batch", |b| {
+        b.iter(|| {
+            for logs in &batch {
+                black_box(parse_instruction(black_box(logs)).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_instruction);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use solana_pumpfun::parser::{LAMPORTS_PER_SOL, TradeInfo};
+use solana_sdk::pubkey::Pubkey;
+
+/// Trades of one mint coalesced over a `--trade-aggregate-window`.
+#[derive(Debug, Default)]
+pub struct TradeSummary {
+    pub buys: u64,
+    pub sells: u64,
+    // lamports, buys in minus sells out
+    pub net_sol: i128,
+    pub last_price_sol: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct TradeAggregator {
+    // ordered so summaries print in a stable order
+    by_mint: BTreeMap<Pubkey, TradeSummary>,
+}
+
+impl TradeAggregator {
+    pub fn observe(&mut self, trade_info: &TradeInfo) {
+        let summary = self.by_mint.entry(trade_info.mint).or_default();
+        if trade_info.is_buy {
+            summary.buys += 1;
+            summary.net_sol += trade_info.sol_amount as i128;
+        } else {
+            summary.sells += 1;
+            summary.net_sol -= trade_info.sol_amount as i128;
+        }
+        summary.last_price_sol = trade_info.price_sol();
+    }
+
+    /// Summaries since the last drain; mints without trades are left out.
+    pub fn drain(&mut self) -> BTreeMap<Pubkey, TradeSummary> {
+        std::mem::take(&mut self.by_mint)
+    }
+}
+
+impl TradeSummary {
+    pub fn net_sol(&self) -> f64 {
+        self.net_sol as f64 / LAMPORTS_PER_SOL
+    }
+}
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use futures_util::stream::StreamExt;
+use log::{error, warn};
+use yellowstone_grpc_proto::prelude::subscribe_update::UpdateOneof;
+
+use solana_pumpfun::stream;
+
+use crate::cli::{BackfillArgs, SourceKind};
+use crate::pipeline::Pipeline;
+
+const FIRST_RESUBSCRIBE_DELAY: Duration = Duration::from_millis(500);
+const MAX_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(30);
+// a replay can't be left retrying forever, unlike the live stream
+const DEFAULT_MAX_RESUBSCRIBES: u32 = 5;
+
+/// Replays `from_slot..=to_slot` in chunks of `chunk_slots`, one subscription
+/// per chunk, so a wide range is never held open (or buffered) at once.
+/// A chunk whose stream fails or ends early is resubscribed from the last
+/// slot seen, up to `--max-reconnects` times in a row, then backfill fails.
+pub async fn backfill_pumpfun_launches(args: BackfillArgs) -> anyhow::Result<()> {
+    if args.to_slot < args.from_slot {
+        anyhow::bail!(
+            "--to-slot {} is before --from-slot {}",
+            args.to_slot,
+            args.from_slot
+        );
+    }
+
+    if args.stream.auto_buy {
+        anyhow::bail!("--auto-buy would buy long past launches, backfill doesn't support it");
+    }
+    if args.stream.source != SourceKind::Geyser {
+        anyhow::bail!("backfill replays slots over gRPC, it only supports --source geyser");
+    }
+
+    let mut pipeline = Pipeline::from_args(&args.stream)?;
+    let mut client = args.stream.connection.connect().await?;
+    println!("Connected to Geyser at {}", args.stream.connection.endpoint);
+
+    let total_slots = args.to_slot - args.from_slot + 1;
+    let chunk_slots = args.chunk_slots.max(1);
+    let mut chunk_start = args.from_slot;
+    let max_resubscribes = args
+        .stream
+        .max_reconnects
+        .unwrap_or(DEFAULT_MAX_RESUBSCRIBES);
+
+    'chunks: while chunk_start <= args.to_slot {
+        let chunk_end = chunk_start
+            .saturating_add(chunk_slots - 1)
+            .min(args.to_slot);
+        // where a resubscription picks up: the last slot seen, replayed
+        // since it may have been cut off partway; --dedup-window drops its
+        // launches already written
+        let mut resume_from = chunk_start;
+        let mut failed_resubscribes = 0;
+        let mut delay = FIRST_RESUBSCRIBE_DELAY;
+
+        loop {
+            let subscribe_request = stream::with_commitment(
+                stream::backfill_subscribe_request(resume_from),
+                args.stream.commitment,
+            );
+            let subscribe_stream = stream::subscribe(&mut client, subscribe_request).await?;
+            tokio::pin!(subscribe_stream);
+
+            let cut_short = loop {
+                let message = tokio::select! {
+                    message = subscribe_stream.next() => message,
+                    _ = pipeline.tick() => continue,
+                    _ = tokio::signal::ctrl_c() => {
+                        println!("Interrupted, slots {}..={} not backfilled", resume_from, args.to_slot);
+                        break 'chunks;
+                    }
+                };
+                let update = match message {
+                    Some(Ok(update)) => update,
+                    Some(Err(e)) => break Some(format!("stream error: {:?}", e)),
+                    None => break Some("stream ended".to_string()),
+                };
+
+                let slot = match &update.update_oneof {
+                    Some(UpdateOneof::Transaction(tx)) => tx.slot,
+                    Some(UpdateOneof::Slot(slot)) => slot.slot,
+                    _ => continue,
+                };
+                if slot > chunk_end {
+                    break None;
+                }
+                if slot < chunk_start {
+                    continue;
+                }
+                if slot > resume_from {
+                    resume_from = slot;
+                    failed_resubscribes = 0;
+                    delay = FIRST_RESUBSCRIBE_DELAY;
+                }
+
+                if let Err(e) = pipeline.process_update(update).await {
+                    error!("Error processing account update: {:?}", e);
+                }
+            };
+            let Some(cut_short) = cut_short else {
+                break;
+            };
+
+            if failed_resubscribes >= max_resubscribes {
+                pipeline.close().await;
+                anyhow::bail!(
+                    "Backfill stopped at slot {} of {}..={} after {} resubscribes: {}",
+                    resume_from,
+                    chunk_start,
+                    chunk_end,
+                    failed_resubscribes,
+                    cut_short
+                );
+            }
+            failed_resubscribes += 1;
+            warn!(
+                "Backfill of {}..={} cut short at slot {} ({}), resubscribing in {:?}",
+                chunk_start, chunk_end, resume_from, cut_short, delay
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RESUBSCRIBE_DELAY);
+        }
+
+        let done = chunk_end - args.from_slot + 1;
+        println!(
+            "Backfilled slots {}..={} ({}/{} slots, {:.1}%, {} launches so far)",
+            chunk_start,
+            chunk_end,
+            done,
+            total_slots,
+            done as f64 * 100.0 / total_slots as f64,
+            pipeline.stats.session.launches
+        );
+        chunk_start = chunk_end + 1;
+    }
+
+    pipeline.stats.print_summary();
+    pipeline.close().await;
+
+    Ok(())
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use solana_pumpfun::parser::{CreateTokenInfo, FirstBuyer, TradeInfo};
+use solana_sdk::pubkey::Pubkey;
+use tokio::time::Instant;
+use tracing::Span;
+
+use crate::cli::StreamArgs;
+
+struct Tracked {
+    watched_at: Instant,
+    buyers: Vec<FirstBuyer>,
+    trades: u32,
+    // set once the launch is done with enrichment and waits on its window
+    held: Option<(CreateTokenInfo, Span)>,
+}
+
+impl Tracked {
+    fn is_closed(&self, tracker: &FirstBuyers) -> bool {
+        self.buyers.len() >= tracker.count
+            || tracker
+                .max_trades
+                .is_some_and(|max_trades| self.trades >= max_trades)
+            || self.watched_at.elapsed() >= tracker.window
+    }
+}
+
+/// `--first-buyers`: the first distinct buyers of each new mint, collected
+/// from its later trades. A launch is held back until its window closes, on
+/// `--first-buyers` buyers, `--first-buyers-max-trades` trades or
+/// `--first-buyers-window`, then goes out with `first_buyers` set.
+pub struct FirstBuyers {
+    count: usize,
+    window: Duration,
+    max_trades: Option<u32>,
+    tracked: HashMap<Pubkey, Tracked>,
+}
+
+impl FirstBuyers {
+    pub fn from_args(args: &StreamArgs) -> Option<Self> {
+        Some(Self {
+            count: args.first_buyers?,
+            window: args.first_buyers_window,
+            max_trades: args.first_buyers_max_trades,
+            tracked: HashMap::new(),
+        })
+    }
+
+    /// Starts collecting buyers for a just-created mint.
+    pub fn watch(&mut self, mint: Pubkey) {
+        self.tracked.insert(
+            mint,
+            Tracked {
+                watched_at: Instant::now(),
+                buyers: vec![],
+                trades: 0,
+                held: None,
+            },
+        );
+    }
+
+    /// Takes a finished launch, handing it back straight away if its window
+    /// already closed.
+    pub fn hold(&mut self, token_info: &CreateTokenInfo) -> Option<(CreateTokenInfo, Span)> {
+        let Some(tracked) = self.tracked.get_mut(&token_info.mint) else {
+            // never watched, e.g. replayed from a log
+            return Some((token_info.clone(), Span::current()));
+        };
+        tracked.held = Some((token_info.clone(), Span::current()));
+        self.release(&token_info.mint)
+    }
+
+    pub fn observe_trade(&mut self, trade_info: &TradeInfo) -> Option<(CreateTokenInfo, Span)> {
+        let tracked = self.tracked.get_mut(&trade_info.mint)?;
+        tracked.trades += 1;
+        if trade_info.is_buy && tracked.buyers.len() < self.count {
+            let sol = trade_info.sol();
+            match tracked
+                .buyers
+                .iter_mut()
+                .find(|buyer| buyer.user == trade_info.user)
+            {
+                Some(buyer) => {
+                    buyer.sol += sol;
+                    buyer.tokens += trade_info.token_amount;
+                }
+                None => tracked.buyers.push(FirstBuyer {
+                    user: trade_info.user,
+                    sol,
+                    tokens: trade_info.token_amount,
+                }),
+            }
+        }
+        self.release(&trade_info.mint)
+    }
+
+    /// Launches whose window ran out, and forgets mints never handed to `hold`.
+    pub fn expire(&mut self) -> Vec<(CreateTokenInfo, Span)> {
+        let window = self.window;
+        // enrichment gave up on these, or dropped them
+        self.tracked.retain(|_, tracked| {
+            tracked.held.is_some() || tracked.watched_at.elapsed() < window * 2
+        });
+        let closed: Vec<Pubkey> = self
+            .tracked
+            .iter()
+            .filter(|(_, tracked)| tracked.held.is_some() && tracked.is_closed(self))
+            .map(|(mint, _)| *mint)
+            .collect();
+        closed
+            .iter()
+            .filter_map(|mint| self.release(mint))
+            .collect()
+    }
+
+    /// Every held launch with the buyers seen so far, at shutdown.
+    pub fn drain(&mut self) -> Vec<(CreateTokenInfo, Span)> {
+        let held: Vec<Pubkey> = self
+            .tracked
+            .iter()
+            .filter(|(_, tracked)| tracked.held.is_some())
+            .map(|(mint, _)| *mint)
+            .collect();
+        let mut drained: Vec<_> = held
+            .iter()
+            .filter_map(|mint| self.tracked.remove(mint).and_then(take_held))
+            .collect();
+        self.tracked.clear();
+        drained.sort_by_key(|(token_info, _)| (token_info.slot, token_info.tx_index));
+        drained
+    }
+
+    fn release(&mut self, mint: &Pubkey) -> Option<(CreateTokenInfo, Span)> {
+        let tracked = self.tracked.get(mint)?;
+        if tracked.held.is_none() || !tracked.is_closed(self) {
+            return None;
+        }
+        self.tracked.remove(mint).and_then(take_held)
+    }
+}
+
+fn take_held(tracked: Tracked) -> Option<(CreateTokenInfo, Span)> {
+    let (mut token_info, span) = tracked.held?;
+    token_info.first_buyers = Some(tracked.buyers);
+    Some((token_info, span))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(30);
+
+    fn tracker(count: usize, max_trades: Option<u32>) -> FirstBuyers {
+        FirstBuyers {
+            count,
+            window: WINDOW,
+            max_trades,
+            tracked: HashMap::new(),
+        }
+    }
+
+    fn launch(slot: u64) -> CreateTokenInfo {
+        CreateTokenInfo {
+            mint: Pubkey::new_unique(),
+            slot,
+            ..Default::default()
+        }
+    }
+
+    fn trade(mint: Pubkey, user: Pubkey, is_buy: bool, sol_amount: u64) -> TradeInfo {
+        TradeInfo {
+            mint,
+            user,
+            is_buy,
+            sol_amount,
+            token_amount: sol_amount * 1_000,
+            ..Default::default()
+        }
+    }
+
+    fn buyers(released: Option<(CreateTokenInfo, Span)>) -> Vec<FirstBuyer> {
+        released.unwrap().0.first_buyers.unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closes_at_the_buyer_count() {
+        let mut tracker = tracker(2, None);
+        let token_info = launch(10);
+        tracker.watch(token_info.mint);
+        assert!(tracker.hold(&token_info).is_none());
+
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(
+            tracker
+                .observe_trade(&trade(token_info.mint, first, true, 100))
+                .is_none()
+        );
+        let released = tracker.observe_trade(&trade(token_info.mint, second, true, 200));
+
+        let users: Vec<_> = buyers(released).iter().map(|buyer| buyer.user).collect();
+        assert_eq!(users, [first, second]);
+        assert!(tracker.tracked.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn adds_up_a_repeat_buyer_and_skips_sells() {
+        let mut tracker = tracker(2, Some(4));
+        let token_info = launch(10);
+        tracker.watch(token_info.mint);
+        tracker.hold(&token_info);
+
+        let buyer = Pubkey::new_unique();
+        for (is_buy, sol_amount) in [(true, 1_000_000_000), (false, 7), (true, 500_000_000)] {
+            assert!(
+                tracker
+                    .observe_trade(&trade(token_info.mint, buyer, is_buy, sol_amount))
+                    .is_none()
+            );
+        }
+        let seller = Pubkey::new_unique();
+        // the fourth trade closes the window, a sell or not
+        let released = tracker.observe_trade(&trade(token_info.mint, seller, false, 9));
+
+        assert_eq!(
+            buyers(released),
+            [FirstBuyer {
+                user: buyer,
+                sol: 1.5,
+                tokens: 1_500_000_000_000,
+            }]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closes_after_the_window() {
+        let mut tracker = tracker(5, None);
+        let token_info = launch(10);
+        tracker.watch(token_info.mint);
+        tracker.hold(&token_info);
+
+        tokio::time::advance(WINDOW - Duration::from_secs(1)).await;
+        assert!(tracker.expire().is_empty());
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        let expired = tracker.expire();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0.first_buyers, Some(vec![]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hands_back_an_unwatched_launch() {
+        let mut tracker = tracker(5, None);
+        let token_info = launch(10);
+
+        let (released, _) = tracker.hold(&token_info).unwrap();
+        assert_eq!(released, token_info);
+        assert!(tracker.tracked.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drains_in_slot_order() {
+        let mut tracker = tracker(5, None);
+        let launches = [launch(12), launch(10), launch(11)];
+        for token_info in &launches {
+            tracker.watch(token_info.mint);
+            tracker.hold(token_info);
+        }
+        // watched but never handed to `hold`, nothing to drain
+        tracker.watch(Pubkey::new_unique());
+
+        let slots: Vec<_> = tracker
+            .drain()
+            .into_iter()
+            .map(|(token_info, _)| token_info.slot)
+            .collect();
+        assert_eq!(slots, [10, 11, 12]);
+        assert!(tracker.tracked.is_empty());
+    }
+}
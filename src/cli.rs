@@ -1,13 +1,762 @@
-use clap::{Parser, Subcommand};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use solana_pumpfun::parser::{
+    Commitment, DEFAULT_MAX_FIELD_LEN, PUMPFUN_PROGRAM_ID, PUMPSWAP_PROGRAM_ID,
+};
+use solana_pumpfun::stream::{self, TlsOptions};
+use solana_pumpfun::utils::OutputFormat;
+use solana_sdk::pubkey::Pubkey;
+use tracing_subscriber::filter::LevelFilter;
+use yellowstone_grpc_client::{GeyserGrpcClient, Interceptor};
+
+use crate::display::PubkeyStyle;
+use crate::filter::Shard;
+use crate::logging::{LogFormat, parse_level};
+use crate::projection::FieldProjection;
+use crate::sinks::file::Partition;
+use crate::sinks::{BatchPolicy, SINK_NAMES, SinkSpec};
+use crate::template::LaunchTemplate;
+
+// const DEFAULT_GEYSER_ENDPOINT: &str = "https://solana-yellowstone-grpc.publicnode.com:443";
+const DEFAULT_GEYSER_ENDPOINT: &str = "https://printworld.shyft.to";
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 pub struct Cli {
     #[clap(subcommand)]
     pub command: Commands,
+
+    /// Level of operational logs on stderr (off, error, warn, info, debug, trace)
+    #[clap(long, global = true, default_value = "info", value_parser = parse_level)]
+    pub log_level: LevelFilter,
+
+    /// Also write operational logs, without ANSI colors, to this file
+    #[clap(long, global = true)]
+    pub log_file: Option<String>,
+
+    /// Level of operational logs written to `--log-file`
+    #[clap(long, global = true, default_value = "info", value_parser = parse_level)]
+    pub log_file_level: LevelFilter,
+
+    /// Format of operational logs on stderr and in `--log-file`
+    #[clap(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Export tracing spans over OTLP/gRPC to this collector, e.g. http://localhost:4317
+    #[cfg(feature = "otel")]
+    #[clap(long, global = true)]
+    pub otel_endpoint: Option<String>,
 }
 
+// parsed once at startup, the size difference doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Commands {
-    Stream,
+    Stream(StreamArgs),
+    /// Process the launches of a past slot range, for providers that replay history
+    Backfill(BackfillArgs),
+    /// Print the records of a launch log as JSON lines
+    Replay(ReplayArgs),
+    /// Check connectivity, auth and streaming against the endpoint
+    Doctor(DoctorArgs),
+}
+
+#[derive(Args, Debug)]
+// --auto-buy needs at least one of --max-buys / --max-spend-sol
+#[clap(group(ArgGroup::new("buy_limit").multiple(true)))]
+pub struct StreamArgs {
+    /// Drop launches created before this time (RFC3339, or a duration ago like
+    /// `2h`), by the create's block time when the event carries it
+    #[clap(long, value_parser = parse_time_bound)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Drop launches created after this time, by block time like `--since`,
+    /// and stop a live stream once it passes (RFC3339, or a duration like
+    /// `2h` ago / `+30m` from now)
+    #[clap(long, value_parser = parse_time_bound)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Keep launches whose event data is truncated, as partial records with
+    /// whatever fields could be decoded (at least name and symbol)
+    #[clap(long)]
+    pub allow_partial: bool,
+
+    /// Reject a launch whose event declares a string field (name, symbol,
+    /// uri) longer than this many bytes, before reading it
+    #[clap(long, default_value_t = DEFAULT_MAX_FIELD_LEN, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub max_field_len: usize,
+
+    /// Submit a Pumpfun buy for every launch that passes the filters, live
+    /// `--source geyser` only. Spends real SOL; requires --i-understand-the-risk
+    /// and a --max-buys or --max-spend-sol cap
+    #[clap(
+        long,
+        requires_all = ["keypair_path", "buy_sol_amount", "i_understand_the_risk", "buy_limit"]
+    )]
+    pub auto_buy: bool,
+
+    /// Keypair file that signs and pays for auto-buys
+    #[clap(long)]
+    pub keypair_path: Option<String>,
+
+    /// SOL to spend per auto-buy
+    #[clap(long, value_parser = parse_sol_amount)]
+    pub buy_sol_amount: Option<f64>,
+
+    /// Stop auto-buying after this many buys
+    #[clap(long, group = "buy_limit", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_buys: Option<u64>,
+
+    /// Stop auto-buying once this much SOL is committed, each buy counted at
+    /// its maximum cost with slippage
+    #[clap(long, group = "buy_limit", value_parser = parse_sol_amount)]
+    pub max_spend_sol: Option<f64>,
+
+    /// Maximum slippage over the initial curve price, in basis points
+    #[clap(long, default_value_t = 500)]
+    pub slippage_bps: u16,
+
+    /// Priority fee for auto-buys, in micro-lamports per compute unit
+    #[clap(long, default_value_t = 0)]
+    pub priority_fee: u64,
+
+    /// Compute unit limit for auto-buy transactions
+    #[clap(long, default_value_t = 120_000)]
+    pub compute_unit_limit: u32,
+
+    /// Solana RPC endpoint used to send transactions
+    #[clap(long, default_value = "https://api.mainnet-beta.solana.com")]
+    pub rpc_url: String,
+
+    /// Confirm that --auto-buy trades real funds
+    #[clap(long)]
+    pub i_understand_the_risk: bool,
+
+    /// Also send launches to this output (repeatable): `stdout-jsonl`,
+    /// `file:<path>` (Borsh for a `.borsh` path, else JSON) or `webhook:<url>`.
+    /// The console printout and `--output` log stay on unless `--quiet` / `--no-log`
+    #[clap(long, value_name = "SINK", value_parser = SinkSpec::parse)]
+    pub sink: Vec<SinkSpec>,
+
+    /// Publish each launch as JSON to Redis pub/sub at this URL
+    #[clap(long)]
+    pub redis_url: Option<String>,
+
+    /// Redis pub/sub channel for `--redis-url`
+    #[clap(long, default_value = "pumpfun:launches")]
+    pub redis_channel: String,
+
+    /// Publish each launch as JSON to the NATS server at this URL, e.g.
+    /// `nats://localhost:4222`, under `--nats-subject`
+    #[cfg(feature = "nats")]
+    #[clap(long)]
+    pub nats_url: Option<String>,
+
+    /// Subject prefix for `--nats-url`; launches go to `<subject>.<creator>`
+    #[cfg(feature = "nats")]
+    #[clap(long, default_value = "pumpfun.launch")]
+    pub nats_subject: String,
+
+    /// Publish each launch as JSON to this Google Cloud Pub/Sub topic,
+    /// `projects/<project>/topics/<topic>`, using Application Default Credentials
+    #[cfg(feature = "pubsub")]
+    #[clap(long)]
+    pub pubsub_topic: Option<String>,
+
+    /// Publish each launch as JSON to this AWS SNS topic, using the AWS SDK's
+    /// default credentials and region
+    #[cfg(feature = "sns")]
+    #[clap(long)]
+    pub sns_topic_arn: Option<String>,
+
+    /// Program to stream and parse (repeatable): Pumpfun, and pump-swap for
+    /// migrations and post-migration swaps
+    #[clap(long = "program-id", value_name = "PROGRAM", value_parser = parse_program_id, default_values_t = [PUMPFUN_PROGRAM_ID])]
+    pub program_ids: Vec<Pubkey>,
+
+    /// Follow only the Buy/Sell trades of this mint instead of new launches (repeatable)
+    #[clap(long = "trades-for", value_name = "MINT")]
+    pub trades_for: Vec<Pubkey>,
+
+    /// With `--trades-for`, print one summary per mint per window (e.g. `1s`)
+    /// instead of every trade
+    #[clap(long, value_parser = humantime::parse_duration, requires = "trades_for")]
+    pub trade_aggregate_window: Option<Duration>,
+
+    /// Fetch each launch's off-chain metadata JSON from its uri before persisting it
+    #[clap(long)]
+    pub fetch_metadata: bool,
+
+    /// Flag launches as `suspected_farm` once this many this session share
+    /// their metadata (the fetched document with `--fetch-metadata`, else the uri)
+    #[clap(long, value_parser = clap::value_parser!(u32).range(2..))]
+    pub flag_metadata_reuse: Option<u32>,
+
+    /// Metadata requests allowed in flight at once with `--fetch-metadata`
+    #[clap(long, default_value_t = 8)]
+    pub metadata_concurrency: usize,
+
+    /// Instead of streaming, send the records of this existing log through the
+    /// filters and sinks, e.g. to test a sink offline
+    #[clap(long)]
+    pub tail: Option<String>,
+
+    /// With `--tail`, keep watching the log and send records as they're added
+    #[clap(long, requires = "tail")]
+    pub follow: bool,
+
+    /// Serve `GET /healthz` and `GET /status` on this address, e.g. 127.0.0.1:8080
+    #[clap(long)]
+    pub health_addr: Option<SocketAddr>,
+
+    /// File launches are written to [default: create_token_log.json, or .borsh with `--format borsh`].
+    /// With `--partition-by`, the directory the files go in [default: launches]
+    #[clap(long)]
+    pub output: Option<String>,
+
+    /// Split the log into one file per day or per creator under `--output`
+    #[clap(long, value_enum, default_value_t = Partition::None, conflicts_with = "tail")]
+    pub partition_by: Partition,
+
+    /// Don't write the `--output` log, e.g. when only feeding sinks
+    #[clap(long, conflicts_with = "output")]
+    pub no_log: bool,
+
+    /// Don't print launches to the console; warnings and errors are still logged
+    #[clap(long)]
+    pub quiet: bool,
+
+    /// Encoding of `--output`
+    #[clap(long, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+
+    /// Indent the JSON log; `--pretty=false` writes it compact, about half the size
+    #[clap(long, action = clap::ArgAction::Set, num_args = 0..=1, default_value_t = true, default_missing_value = "true")]
+    pub pretty: bool,
+
+    /// Sync the log file to disk after every write, so no written launch is
+    /// lost to a power cut. Slower: raise `--sink-batch-size file=N` to sync
+    /// once per N launches
+    #[clap(long)]
+    pub fsync: bool,
+
+    /// Only emit these record fields (comma separated, e.g. `name,symbol,mint`)
+    /// on the console, in the JSON log and to sinks
+    #[clap(long, value_parser = FieldProjection::parse)]
+    pub fields: Option<FieldProjection>,
+
+    /// Flag launches whose symbol matches, or whose name is within this many
+    /// edits of, an earlier launch this session as `similar_to`
+    #[clap(long)]
+    pub copycat_threshold: Option<usize>,
+
+    /// Check via `--rpc-url` that each mint exists and is owned by the token
+    /// program, recording the result as `mint_verified`
+    #[clap(long)]
+    pub verify_mint_onchain: bool,
+
+    /// Look up each creator's history via `--rpc-url` and record whether it's a
+    /// fresh wallet as `fresh_wallet`. Results are cached per wallet
+    #[clap(long)]
+    pub check_fresh_wallet: bool,
+
+    /// `--check-fresh-wallet`: fresh means fewer than this many transactions
+    #[clap(long, default_value_t = 20, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=1000))]
+    pub fresh_wallet_max_txs: usize,
+
+    /// `--check-fresh-wallet`: and a first transaction no older than this
+    #[clap(long, value_parser = humantime::parse_duration)]
+    pub fresh_wallet_max_age: Option<Duration>,
+
+    /// Drop launches whose `--fetch-metadata` document has no image, or
+    /// couldn't be fetched
+    #[clap(long, requires = "fetch_metadata")]
+    pub require_complete_metadata: bool,
+
+    /// Drop launches whose mint fails `--verify-mint-onchain` instead of flagging them
+    #[clap(long, requires = "verify_mint_onchain")]
+    pub drop_unverified: bool,
+
+    /// URL returning the SOL/USD price, as a bare number or JSON (see
+    /// `--sol-price-pointer`), used to record `initial_market_cap_usd`
+    #[clap(long)]
+    pub sol_price_source: Option<String>,
+
+    /// JSON pointer to the price in the `--sol-price-source` response, e.g. `/solana/usd`
+    #[clap(long, requires = "sol_price_source")]
+    pub sol_price_pointer: Option<String>,
+
+    /// How often to refresh the SOL price
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "30s")]
+    pub sol_price_refresh: Duration,
+
+    /// Poll `--rpc-url` until each launch's transaction is finalized, recording
+    /// the result as `finalized`. Launches reach the log and sinks once checked
+    #[clap(long)]
+    pub confirm_finality: bool,
+
+    /// How long a launch may take to finalize before it's recorded as not finalized
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "60s")]
+    pub finality_timeout: Duration,
+
+    /// Drop launches that don't finalize instead of flagging them
+    #[clap(long, requires = "confirm_finality")]
+    pub drop_unfinalized: bool,
+
+    /// Drop a launch seen again in the same transaction within this long, as
+    /// redelivered after a reconnect; `0s` turns it off
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "10m")]
+    pub dedup_window: Duration,
+
+    /// Keep at most this many launches per second, dropping the rest of a
+    /// burst. Kept launches are marked `sampled`
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_per_second: Option<u32>,
+
+    /// Hold each launch back from the log file and sinks until its mint trades
+    /// in a later transaction, dropping it if none comes within `--gate-timeout`
+    #[clap(long, conflicts_with = "tail")]
+    pub require_first_trade: bool,
+
+    /// Hold each launch back from the log file and sinks until its creator has
+    /// made this many launches, counted from its oldest launch still held,
+    /// dropping it after `--gate-timeout`
+    #[clap(long, conflicts_with = "tail")]
+    pub min_launches_to_write: Option<usize>,
+
+    /// How long `--require-first-trade` and `--min-launches-to-write` hold a launch
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "60s")]
+    pub gate_timeout: Duration,
+
+    /// Hold each launch back until its first this-many distinct buyers are
+    /// seen (or the window below closes), and record them as `first_buyers`
+    #[clap(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..), conflicts_with = "tail")]
+    pub first_buyers: Option<usize>,
+
+    /// Longest `--first-buyers` collects buyers after the create
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "30s")]
+    pub first_buyers_window: Duration,
+
+    /// Also close the `--first-buyers` window after this many trades of the mint
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub first_buyers_max_trades: Option<u32>,
+
+    /// Print the log lines and raw `Program data:` of each matched invocation
+    /// next to the parsed launch
+    #[clap(long)]
+    pub print_raw: bool,
+
+    /// Pipe each launch through this command before the log file and sinks:
+    /// one JSON record per line on its stdin, answered by one line on its
+    /// stdout with the record to write (computed fields go in `extra`) or `null`
+    /// to drop it. Run with `sh -c`, kept running for the whole stream
+    #[clap(long, value_name = "COMMAND")]
+    pub transform: Option<String>,
+
+    /// How long `--transform` gets per launch before it's restarted and the
+    /// launch written unchanged
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "1s")]
+    pub transform_timeout: Duration,
+
+    /// Print each launch as this one-line template instead of the block, e.g.
+    /// `{symbol} {mint} by {creator} @ {price}`. Placeholders: name, symbol,
+    /// mint, creator, bonding_curve, uri, slot, price, market_cap, signature, latency
+    #[clap(long, value_parser = LaunchTemplate::parse)]
+    pub template: Option<LaunchTemplate>,
+
+    /// Shorten pubkeys in console output to `AbCd…WxYz`: `N` characters at
+    /// each end, or `PREFIX:SUFFIX`. The log file and sinks keep full keys
+    #[clap(long, value_name = "N|PREFIX:SUFFIX", value_parser = PubkeyStyle::parse_abbrev)]
+    pub pubkey_abbrev: Option<PubkeyStyle>,
+
+    /// Explorer URL printed with each launch, `{signature}` is replaced by the
+    /// transaction signature
+    #[clap(long, value_parser = parse_link_template, default_value = "https://solscan.io/tx/{signature}")]
+    pub link_template: String,
+
+    /// Also print launch counts and the top creators and symbols for each
+    /// window of this length (e.g. `5m`)
+    #[clap(long, value_parser = humantime::parse_duration)]
+    pub summary_interval: Option<Duration>,
+
+    /// Count every Pumpfun instruction by name, not just creates and trades,
+    /// and print the counts with each summary. A new name means a new
+    /// instruction the parser may need to learn
+    #[clap(long)]
+    pub tally_instructions: bool,
+
+    /// Rows in the top creators and symbols tables, 0 hides them
+    #[clap(long, default_value_t = 5)]
+    pub summary_top: usize,
+
+    /// Raise a `launch_surge` event when a minute's launch count passes this
+    /// many times the baseline (an EWMA of earlier minutes), e.g. `3`
+    #[clap(long, value_parser = parse_surge_multiplier, conflicts_with = "tail")]
+    pub surge_multiplier: Option<f64>,
+
+    /// Weight of the latest minute in the `--surge-multiplier` baseline
+    #[clap(long, value_parser = parse_surge_alpha, default_value_t = 0.1)]
+    pub surge_alpha: f64,
+
+    /// Fewest launches in a minute that count as a surge, however low the baseline
+    #[clap(long, default_value_t = 10)]
+    pub surge_min_rate: u64,
+
+    /// Also POST each `launch_surge` event as JSON to this URL
+    #[clap(long, requires = "surge_multiplier")]
+    pub surge_webhook: Option<String>,
+
+    /// Commitment to subscribe at [default: the provider's]. Records are
+    /// tagged with it; `processed` is fastest but can include launches later
+    /// dropped on a fork
+    #[clap(long, value_enum)]
+    pub commitment: Option<Commitment>,
+
+    /// Where updates come from
+    #[clap(long, value_enum, default_value_t = SourceKind::Geyser)]
+    pub source: SourceKind,
+
+    /// The file played back with `--source recording`
+    #[clap(long, value_name = "PATH", required_if_eq("source", "recording"))]
+    pub recording: Option<PathBuf>,
+
+    /// Append every update as received, before parsing, to this file, for
+    /// `--source recording` to play back later. Length-prefixed protobuf
+    #[clap(long, value_name = "PATH", conflicts_with = "tail")]
+    pub record: Option<PathBuf>,
+
+    /// Exit with an error after this many consecutive failed reconnects to
+    /// Geyser, for a supervisor to restart [default: retry forever, 5 for
+    /// `backfill`]
+    #[clap(long)]
+    pub max_reconnects: Option<u32>,
+
+    #[clap(flatten)]
+    pub connection: ConnectionArgs,
+
+    #[cfg(feature = "kafka")]
+    #[clap(flatten)]
+    pub kafka: KafkaArgs,
+
+    /// Hand launches to each sink (the log file included) in batches of up to
+    /// this many. The log file writes each batch sorted by (slot, transaction
+    /// index)
+    #[clap(long, default_value_t = 1)]
+    pub batch_size: usize,
+
+    /// Longest a launch waits for its sink batch to fill, with `--batch-size`
+    /// above 1. The log file sorts by slot within these batches, with no
+    /// reorder window of its own, and `--cursor-file` is written no more often
+    /// than this
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "500ms")]
+    pub flush_interval: Duration,
+
+    /// `--batch-size` for one sink, e.g. `file=50` (sinks: file, redis, nats, pubsub, sns, stdout, webhook)
+    #[clap(long, value_parser = parse_sink_option::<usize>)]
+    pub sink_batch_size: Vec<(String, usize)>,
+
+    /// `--flush-interval` for one sink, e.g. `redis=0s`
+    #[clap(long, value_parser = parse_sink_option::<humantime::Duration>)]
+    pub sink_flush_interval: Vec<(String, humantime::Duration)>,
+
+    /// Only keep launches by creators listed in this file (one pubkey per
+    /// line, reloaded on SIGHUP)
+    #[clap(long)]
+    pub creator_allowlist: Option<PathBuf>,
+
+    /// Drop launches by creators listed in this file, even if allowlisted
+    /// (reloaded on SIGHUP)
+    #[clap(long)]
+    pub creator_blocklist: Option<PathBuf>,
+
+    /// Only keep this instance's share of launches, `i/n` by a stable hash of
+    /// the mint: run `0/n` through `n-1/n` to split the stream across machines
+    #[clap(long, value_name = "I/N", value_parser = Shard::parse)]
+    pub shard: Option<Shard>,
+
+    /// Drop launches whose name is shorter than this many characters
+    #[clap(long, default_value_t = 0)]
+    pub min_name_len: usize,
+
+    /// Drop launches whose symbol is shorter than this many characters
+    #[clap(long, default_value_t = 0)]
+    pub min_symbol_len: usize,
+
+    /// Drop launches with a blank name or symbol, i.e. both minimums at least 1
+    #[clap(long)]
+    pub reject_empty: bool,
+
+    /// Only keep launches whose symbol matches this shell-style pattern, e.g.
+    /// `MOON*` or `*INU` (repeatable, any may match). Case-insensitive unless
+    /// `--glob-case-sensitive`
+    #[clap(long)]
+    pub symbol_glob: Vec<String>,
+
+    /// Only keep launches whose name matches this shell-style pattern
+    /// (repeatable, any may match)
+    #[clap(long)]
+    pub name_glob: Vec<String>,
+
+    /// Match `--symbol-glob` and `--name-glob` case-sensitively
+    #[clap(long)]
+    pub glob_case_sensitive: bool,
+
+    /// Keep the highest processed slot in this file, held back to the slot of
+    /// any launch still being enriched or held, written atomically every
+    /// second or so and on shutdown
+    #[clap(long, conflicts_with = "tail")]
+    pub cursor_file: Option<PathBuf>,
+
+    /// Subscribe from the slot in `--cursor-file`, replaying what was missed
+    /// while stopped. Needs a provider that keeps history
+    #[clap(long, requires = "cursor_file")]
+    pub auto_resume: bool,
+
+    /// Drop launches whose metadata `uri` is empty or not a usable address: an
+    /// http(s) URL with a domain, or `ipfs://` / `ar://`
+    #[clap(long)]
+    pub require_uri: bool,
+
+    /// Only keep launches whose metadata `uri` is hosted on this domain or a
+    /// subdomain of it (repeatable)
+    #[clap(long, value_parser = parse_domain)]
+    pub uri_domain_allow: Vec<String>,
+
+    /// Drop launches whose metadata `uri` is hosted on this domain or a
+    /// subdomain of it, even if allowed (repeatable)
+    #[clap(long, value_parser = parse_domain)]
+    pub uri_domain_block: Vec<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SourceKind {
+    /// subscribe to `--endpoint` over Yellowstone gRPC
+    #[default]
+    Geyser,
+    /// consume protobuf `SubscribeUpdate`s from `--kafka-topic`
+    #[cfg(feature = "kafka")]
+    Kafka,
+    /// play back a `--record` file given as `--recording`, then exit
+    Recording,
+}
+
+/// The Kafka topic read with `--source kafka`.
+#[cfg(feature = "kafka")]
+#[derive(Args, Debug)]
+pub struct KafkaArgs {
+    /// Comma separated bootstrap brokers, e.g. `localhost:9092`
+    #[clap(long, required_if_eq("source", "kafka"))]
+    pub kafka_brokers: Option<String>,
+
+    /// Topic of protobuf-encoded Geyser `SubscribeUpdate` messages
+    #[clap(long, required_if_eq("source", "kafka"))]
+    pub kafka_topic: Option<String>,
+
+    /// Consumer group, so several instances can share the topic's partitions
+    #[clap(long, default_value = "solana-pumpfun")]
+    pub kafka_group_id: String,
+}
+
+/// How to reach the Geyser endpoint, shared by every command that connects.
+#[derive(Args, Debug)]
+pub struct ConnectionArgs {
+    /// Yellowstone gRPC endpoint
+    #[clap(long, default_value = DEFAULT_GEYSER_ENDPOINT)]
+    pub endpoint: String,
+
+    /// Auth token sent as `x-token`, for endpoints that require one
+    #[clap(long)]
+    pub x_token: Option<String>,
+
+    /// PEM CA certificate to trust for the Geyser endpoint instead of the native roots
+    #[clap(long)]
+    pub ca_cert: Option<PathBuf>,
+
+    /// PEM client certificate for mutual TLS with the Geyser endpoint
+    #[clap(long, requires = "client_key")]
+    pub client_cert: Option<PathBuf>,
+
+    /// PEM private key for `--client-cert`
+    #[clap(long, requires = "client_cert")]
+    pub client_key: Option<PathBuf>,
+}
+
+impl ConnectionArgs {
+    pub fn tls_options(&self) -> TlsOptions {
+        TlsOptions {
+            ca_cert: self.ca_cert.clone(),
+            client_cert: self.client_cert.clone(),
+            client_key: self.client_key.clone(),
+        }
+    }
+
+    pub async fn connect(&self) -> anyhow::Result<GeyserGrpcClient<impl Interceptor + use<>>> {
+        Ok(stream::connect(&self.endpoint, self.x_token.as_deref(), &self.tls_options()).await?)
+    }
+}
+
+impl StreamArgs {
+    pub fn output_path(&self) -> &str {
+        self.output
+            .as_deref()
+            .unwrap_or_else(|| self.format.default_path())
+    }
+
+    /// Batching for the sink called `name`, with its overrides applied.
+    pub fn batch_policy(&self, name: &str) -> BatchPolicy {
+        let max_size = self
+            .sink_batch_size
+            .iter()
+            .rev()
+            .find(|(sink, _)| sink == name)
+            .map_or(self.batch_size, |(_, size)| *size);
+        let max_delay = self
+            .sink_flush_interval
+            .iter()
+            .rev()
+            .find(|(sink, _)| sink == name)
+            .map_or(self.flush_interval, |(_, interval)| (*interval).into());
+
+        BatchPolicy {
+            max_size,
+            max_delay,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct BackfillArgs {
+    /// First slot of the range
+    #[clap(long)]
+    pub from_slot: u64,
+
+    /// Last slot of the range, inclusive
+    #[clap(long)]
+    pub to_slot: u64,
+
+    /// Slots replayed per subscription
+    #[clap(long, default_value_t = 1_000)]
+    pub chunk_slots: u64,
+
+    #[clap(flatten)]
+    pub stream: StreamArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    #[clap(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// How long to wait for the first streamed update
+    #[clap(long, value_parser = humantime::parse_duration, default_value = "30s")]
+    pub timeout: Duration,
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Log written by `stream`
+    pub path: String,
+
+    /// Encoding of `path`
+    #[clap(long, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+}
+
+// `<sink>=<value>` for per-sink overrides, the sink must exist
+fn parse_sink_option<T>(value: &str) -> Result<(String, T), String>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let (sink, option) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected <sink>=<value>, got {:?}", value))?;
+    if !SINK_NAMES.contains(&sink) {
+        return Err(format!(
+            "unknown sink {:?}, expected one of {}",
+            sink,
+            SINK_NAMES.join(", ")
+        ));
+    }
+    let option = option
+        .parse()
+        .map_err(|e| format!("invalid value for {}: {}", sink, e))?;
+
+    Ok((sink.to_string(), option))
+}
+
+// only programs the parser has events for
+fn parse_program_id(value: &str) -> Result<Pubkey, String> {
+    let program_id: Pubkey = value.parse().map_err(|e| format!("{}", e))?;
+    if ![PUMPFUN_PROGRAM_ID, PUMPSWAP_PROGRAM_ID].contains(&program_id) {
+        return Err(format!(
+            "no parser for {}, expected {} (Pumpfun) or {} (pump-swap)",
+            program_id, PUMPFUN_PROGRAM_ID, PUMPSWAP_PROGRAM_ID
+        ));
+    }
+    Ok(program_id)
+}
+
+// hosts compare lowercased, as `url` normalizes them
+fn parse_domain(value: &str) -> Result<String, String> {
+    let domain = value.trim().trim_matches('.').to_ascii_lowercase();
+    if domain.is_empty() || domain.contains(['/', ':']) {
+        return Err(format!(
+            "expected a bare domain like ipfs.io, got {:?}",
+            value
+        ));
+    }
+    Ok(domain)
+}
+
+fn parse_surge_multiplier(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(multiplier) if multiplier > 1.0 => Ok(multiplier),
+        _ => Err(format!("expected a multiplier above 1, got {:?}", value)),
+    }
+}
+
+fn parse_sol_amount(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(sol) if sol.is_finite() && sol > 0.0 => Ok(sol),
+        _ => Err(format!("expected a SOL amount above 0, got {:?}", value)),
+    }
+}
+
+fn parse_surge_alpha(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(alpha) if alpha > 0.0 && alpha <= 1.0 => Ok(alpha),
+        _ => Err(format!("expected a weight in (0, 1], got {:?}", value)),
+    }
+}
+
+fn parse_link_template(value: &str) -> Result<String, String> {
+    if !value.contains("{signature}") {
+        return Err(format!("{:?} has no {{signature}} placeholder", value));
+    }
+    Ok(value.to_string())
+}
+
+fn parse_time_bound(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let (future, duration) = match value.strip_prefix('+') {
+        Some(duration) => (true, duration),
+        None => (false, value),
+    };
+    let duration = humantime::parse_duration(duration)
+        .map_err(|e| format!("expected RFC3339 time or duration, got {:?}: {}", value, e))?;
+    let duration = chrono::Duration::from_std(duration).map_err(|e| e.to_string())?;
+
+    Ok(if future {
+        Utc::now() + duration
+    } else {
+        Utc::now() - duration
+    })
 }
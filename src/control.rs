@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::time::Instant;
+
+/// Stream-level frames sent to the sinks alongside launches: lifecycle
+/// events, so downstream consumers can mark a possible gap and backfill it,
+/// and `--surge-multiplier` alerts.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlEvent {
+    StreamStarted {
+        at: DateTime<Utc>,
+    },
+    StreamReconnected {
+        at: DateTime<Utc>,
+        // ms and slots between the last update before the reconnect and the first after
+        gap_estimate_ms: u64,
+        last_slot: Option<u64>,
+        resumed_slot: Option<u64>,
+    },
+    StreamStopped {
+        at: DateTime<Utc>,
+        launches: u64,
+    },
+    LaunchSurge {
+        at: DateTime<Utc>,
+        // the minute just closed, against the EWMA of the minutes before it
+        launches_per_minute: u64,
+        baseline_per_minute: f64,
+        multiplier: f64,
+    },
+}
+
+impl ControlEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ControlEvent::StreamStarted { .. } => "stream_started",
+            ControlEvent::StreamReconnected { .. } => "stream_reconnected",
+            ControlEvent::StreamStopped { .. } => "stream_stopped",
+            ControlEvent::LaunchSurge { .. } => "launch_surge",
+        }
+    }
+}
+
+/// Turns a source's reconnect count, checked on every update, into
+/// `StreamReconnected` events with the gap around each reconnect.
+#[derive(Default)]
+pub struct GapTracker {
+    reconnects: u64,
+    last_update_at: Option<Instant>,
+    last_slot: Option<u64>,
+}
+
+impl GapTracker {
+    pub fn observe(&mut self, reconnects: u64, slot: Option<u64>) -> Option<ControlEvent> {
+        let now = Instant::now();
+        let event = (reconnects > self.reconnects).then(|| ControlEvent::StreamReconnected {
+            at: Utc::now(),
+            gap_estimate_ms: self
+                .last_update_at
+                .map_or(0, |at| now.duration_since(at).as_millis() as u64),
+            last_slot: self.last_slot,
+            resumed_slot: slot,
+        });
+
+        self.reconnects = reconnects;
+        self.last_update_at = Some(now);
+        if slot.is_some() {
+            self.last_slot = slot;
+        }
+        event
+    }
+}
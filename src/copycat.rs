@@ -0,0 +1,151 @@
+use std::collections::VecDeque;
+
+use solana_pumpfun::parser::CreateTokenInfo;
+use solana_sdk::pubkey::Pubkey;
+
+// launches a new one is compared against
+const HISTORY: usize = 1_000;
+
+struct SeenLaunch {
+    name: String,
+    symbol: String,
+    mint: Pubkey,
+}
+
+/// Flags launches whose symbol matches, or whose name is within `threshold`
+/// edits of, a launch seen earlier in the session.
+pub struct CopycatDetector {
+    threshold: usize,
+    recent: VecDeque<SeenLaunch>,
+}
+
+impl CopycatDetector {
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            recent: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    /// The mint of the most recent similar launch, then remembers `token_info`.
+    pub fn observe(&mut self, token_info: &CreateTokenInfo) -> Option<Pubkey> {
+        let name = normalize(&token_info.name);
+        let symbol = normalize(&token_info.symbol);
+
+        let similar_to = self
+            .recent
+            .iter()
+            .rev()
+            .find(|seen| {
+                (!symbol.is_empty() && seen.symbol == symbol)
+                    || (!name.is_empty() && levenshtein(&seen.name, &name) <= self.threshold)
+            })
+            .map(|seen| seen.mint);
+
+        if self.recent.len() == HISTORY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(SeenLaunch {
+            name,
+            symbol,
+            mint: token_info.mint,
+        });
+
+        similar_to
+    }
+}
+
+// case, spacing and punctuation are the cheapest things for a clone to change
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch(name: &str, symbol: &str) -> CreateTokenInfo {
+        CreateTokenInfo {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            mint: Pubkey::new_unique(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flags_the_same_symbol_whatever_its_case_and_punctuation() {
+        let mut copycats = CopycatDetector::new(0);
+        let original = launch("Moon Cat", "MCAT");
+
+        assert_eq!(copycats.observe(&original), None);
+        assert_eq!(
+            copycats.observe(&launch("Something Else", "m.cat")),
+            Some(original.mint)
+        );
+    }
+
+    #[test]
+    fn flags_a_name_within_the_threshold() {
+        let mut copycats = CopycatDetector::new(2);
+        let original = launch("Moon Cat", "MCAT");
+        copycats.observe(&original);
+
+        // two edits, then three
+        assert_eq!(
+            copycats.observe(&launch("Moon Hat!", "MHAT")),
+            Some(original.mint)
+        );
+        assert_eq!(copycats.observe(&launch("Noon Bats", "NBATS")), None);
+    }
+
+    #[test]
+    fn points_at_the_most_recent_match() {
+        let mut copycats = CopycatDetector::new(0);
+        copycats.observe(&launch("Moon Cat", "MCAT"));
+        let second = launch("Moon Cat", "MCAT");
+        copycats.observe(&second);
+
+        assert_eq!(
+            copycats.observe(&launch("Moon Cat", "MCAT")),
+            Some(second.mint)
+        );
+    }
+
+    // nothing in common but being blank
+    #[test]
+    fn never_matches_on_empty_fields() {
+        let mut copycats = CopycatDetector::new(0);
+        copycats.observe(&launch("", "$$$"));
+
+        assert_eq!(copycats.observe(&launch("!!", "")), None);
+    }
+
+    #[test]
+    fn counts_edits() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+}
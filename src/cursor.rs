@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use log::{info, warn};
+
+/// `--cursor-file`: where `--auto-resume` picks up after a restart, the
+/// highest processed slot or, while a launch from an earlier one is still
+/// being enriched or held, that launch's slot.
+///
+/// Each write records the position reached one write earlier, by when the
+/// launches up to it have had a full interval to leave the sink and log file
+/// buffers.
+pub struct Cursor {
+    path: PathBuf,
+    slot: Option<u64>,
+    // launches not yet committed or dropped, counted by slot
+    outstanding: BTreeMap<u64, usize>,
+    // as of the previous write, what the next one records
+    settled: Option<u64>,
+    written: Option<u64>,
+}
+
+impl Cursor {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            slot: None,
+            outstanding: BTreeMap::new(),
+            settled: None,
+            written: None,
+        }
+    }
+
+    /// The slot recorded in `path`, None when there's no cursor yet.
+    pub fn read(path: &Path) -> anyhow::Result<Option<u64>> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => anyhow::bail!("Failed to read cursor {}: {}", path.display(), e),
+        };
+        let slot = contents.trim().parse().map_err(|e| {
+            anyhow::anyhow!(
+                "Cursor {} holds {:?}, not a slot: {}",
+                path.display(),
+                contents.trim(),
+                e
+            )
+        })?;
+        Ok(Some(slot))
+    }
+
+    pub fn observe(&mut self, slot: u64) {
+        self.slot = self.slot.max(Some(slot));
+    }
+
+    /// Keeps the cursor at or before `slot` until a matching `release`.
+    pub fn hold(&mut self, slot: u64) {
+        *self.outstanding.entry(slot).or_default() += 1;
+    }
+
+    /// A launch from `slot` was committed or dropped. One never held, like a
+    /// `--tail` replay's, is ignored.
+    pub fn release(&mut self, slot: u64) {
+        if let Entry::Occupied(mut held) = self.outstanding.entry(slot) {
+            *held.get_mut() -= 1;
+            if *held.get() == 0 {
+                held.remove();
+            }
+        }
+    }
+
+    /// Writes the position settled since the last call, then settles the current one.
+    pub fn write_settled(&mut self) {
+        if let Some(slot) = self.settled {
+            self.write(slot);
+        }
+        self.settled = self.position();
+    }
+
+    /// Writes the current position, once the sinks and log file have drained.
+    /// Launches still held count, a restart replays them.
+    pub fn close(mut self) {
+        if let Some(slot) = self.position() {
+            self.write(slot);
+            info!("Cursor at slot {} in {}", slot, self.path.display());
+        }
+    }
+
+    // the oldest slot with a launch outstanding, itself replayed on resume,
+    // or else the highest processed
+    fn position(&self) -> Option<u64> {
+        let oldest_held = self.outstanding.keys().next().copied();
+        match (oldest_held, self.slot) {
+            (Some(held), Some(slot)) => Some(held.min(slot)),
+            (held, slot) => held.or(slot),
+        }
+    }
+
+    // a handful of bytes, written inline
+    fn write(&mut self, slot: u64) {
+        if self.written == Some(slot) {
+            return;
+        }
+        match write_atomically(&self.path, slot) {
+            Ok(()) => self.written = Some(slot),
+            Err(e) => warn!("Failed to write cursor {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+/// How often the cursor is written: no sooner than the sink batches let a
+/// launch out.
+pub fn write_interval(flush_interval: Duration) -> Duration {
+    Duration::from_secs(1).max(flush_interval)
+}
+
+// a crash mid-write leaves the previous cursor in place
+fn write_atomically(path: &Path, slot: u64) -> std::io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let mut file = std::fs::File::create(&tmp_path)?;
+    writeln!(file, "{}", slot)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_each_slot_one_write_late() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cursor");
+        let mut cursor = Cursor::new(&path);
+
+        cursor.observe(10);
+        cursor.write_settled();
+        assert_eq!(Cursor::read(&path).unwrap(), None);
+
+        cursor.observe(12);
+        cursor.write_settled();
+        assert_eq!(Cursor::read(&path).unwrap(), Some(10));
+        cursor.write_settled();
+        assert_eq!(Cursor::read(&path).unwrap(), Some(12));
+    }
+
+    #[test]
+    fn stays_at_a_held_launch_until_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cursor");
+        let mut cursor = Cursor::new(&path);
+
+        cursor.hold(10);
+        cursor.observe(10);
+        cursor.observe(20);
+        cursor.write_settled();
+        cursor.write_settled();
+        assert_eq!(Cursor::read(&path).unwrap(), Some(10));
+
+        // still a write late, for the sinks to take it
+        cursor.release(10);
+        cursor.write_settled();
+        assert_eq!(Cursor::read(&path).unwrap(), Some(10));
+        cursor.write_settled();
+        assert_eq!(Cursor::read(&path).unwrap(), Some(20));
+    }
+
+    #[test]
+    fn counts_each_launch_of_a_slot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cursor");
+        let mut cursor = Cursor::new(&path);
+
+        cursor.hold(5);
+        cursor.hold(5);
+        cursor.observe(9);
+        cursor.release(5);
+        // never held
+        cursor.release(7);
+        cursor.close();
+        assert_eq!(Cursor::read(&path).unwrap(), Some(5));
+    }
+
+    #[test]
+    fn rejects_a_cursor_that_is_not_a_slot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cursor");
+        std::fs::write(&path, "not a slot\n").unwrap();
+
+        assert!(Cursor::read(&path).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Live state of a launch's bonding curve, read from its account.
+
+use borsh::BorshDeserialize;
+#[cfg(feature = "stream")]
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+#[cfg(feature = "stream")]
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::PumpfunError;
+#[cfg(feature = "stream")]
+use crate::parser::PUMPFUN_PROGRAM_ID;
+use crate::parser::{LAMPORTS_PER_SOL, PUMPFUN_TOKEN_DECIMALS};
+
+// first 8 bytes of sha256("account:BondingCurve")
+pub const BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [23, 183, 248, 55, 96, 216, 172, 96];
+
+/// The bonding curve account. Newer accounts carry more fields after
+/// `complete`, which are ignored.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize)]
+pub struct BondingCurveState {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    // the curve sold out and migrates, or has migrated, to the AMM
+    pub complete: bool,
+}
+
+impl BondingCurveState {
+    /// Decodes the account data, discriminator included.
+    pub fn from_account_data(data: &[u8]) -> Result<Self, PumpfunError> {
+        let Some((discriminator, mut state)) = data.split_first_chunk::<8>() else {
+            return Err(PumpfunError::Truncated {
+                field: "bonding curve discriminator".to_string(),
+                needed: 8,
+            });
+        };
+        if *discriminator != BONDING_CURVE_DISCRIMINATOR {
+            return Err(PumpfunError::InvalidDiscriminator(*discriminator));
+        }
+
+        BondingCurveState::deserialize(&mut state)
+            .map_err(|e| PumpfunError::Decode(format!("bonding curve account: {}", e)))
+    }
+
+    /// SOL per whole token at the curve's current price.
+    pub fn price_sol(&self) -> f64 {
+        if self.virtual_token_reserves == 0 {
+            return 0.0;
+        }
+        (self.virtual_sol_reserves as f64 / LAMPORTS_PER_SOL)
+            / (self.virtual_token_reserves as f64 / 10f64.powi(PUMPFUN_TOKEN_DECIMALS as i32))
+    }
+
+    pub fn real_sol(&self) -> f64 {
+        self.real_sol_reserves as f64 / LAMPORTS_PER_SOL
+    }
+}
+
+/// Fetches `bonding_curve` (`CreateTokenInfo::bonding_curve`) at the client's commitment.
+#[cfg(feature = "stream")]
+pub async fn fetch_bonding_curve_state(
+    rpc: &RpcClient,
+    bonding_curve: &Pubkey,
+) -> anyhow::Result<BondingCurveState> {
+    let account = rpc
+        .get_account(bonding_curve)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to get account {}: {}", bonding_curve, e))?;
+    if account.owner != PUMPFUN_PROGRAM_ID {
+        anyhow::bail!(
+            "{} is owned by {}, not Pumpfun",
+            bonding_curve,
+            account.owner
+        );
+    }
+
+    Ok(BondingCurveState::from_account_data(&account.data)?)
+}
@@ -0,0 +1,90 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use tokio::time::Instant;
+
+// launches remembered at most, whatever `--dedup-window` is; a day of
+// launches at a busy rate
+const MAX_ENTRIES: usize = 200_000;
+
+/// `--dedup-window`: drops a launch already seen in the same transaction,
+/// as redelivered after a reconnect resumes at an earlier slot. Keyed on
+/// (mint, signature) so only a repeat of the very same transaction counts.
+/// Entries go once unseen for the window, or oldest first past the size cap.
+pub struct LaunchDedup {
+    window: Duration,
+    // last seen, the queue's matching entry is the live one
+    seen: HashMap<(Pubkey, Signature), Instant>,
+    order: VecDeque<((Pubkey, Signature), Instant)>,
+}
+
+impl LaunchDedup {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Records the launch, true when it was already seen within the window.
+    pub fn is_duplicate(&mut self, mint: Pubkey, signature: Signature) -> bool {
+        let now = Instant::now();
+        self.evict(now);
+        let key = (mint, signature);
+        let duplicate = self.seen.insert(key, now).is_some();
+        self.order.push_back((key, now));
+        duplicate
+    }
+
+    fn evict(&mut self, now: Instant) {
+        while let Some(&(key, seen_at)) = self.order.front() {
+            let expired = now.duration_since(seen_at) >= self.window;
+            if !expired && self.seen.len() < MAX_ENTRIES {
+                break;
+            }
+            self.order.pop_front();
+            // a later sighting refreshed it, that one's further back in the queue
+            if self.seen.get(&key) == Some(&seen_at) {
+                self.seen.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    #[tokio::test(start_paused = true)]
+    async fn drops_a_repeat_of_the_same_transaction() {
+        let mut dedup = LaunchDedup::new(WINDOW);
+        let (mint, signature) = (Pubkey::new_unique(), Signature::new_unique());
+
+        assert!(!dedup.is_duplicate(mint, signature));
+        assert!(dedup.is_duplicate(mint, signature));
+        assert!(!dedup.is_duplicate(mint, Signature::new_unique()));
+        assert!(!dedup.is_duplicate(Pubkey::new_unique(), signature));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn forgets_a_launch_unseen_for_the_window() {
+        let mut dedup = LaunchDedup::new(WINDOW);
+        let (mint, signature) = (Pubkey::new_unique(), Signature::new_unique());
+        dedup.is_duplicate(mint, signature);
+
+        tokio::time::advance(WINDOW - Duration::from_secs(1)).await;
+        assert!(dedup.is_duplicate(mint, signature));
+
+        // the repeat just now counts as a sighting
+        tokio::time::advance(WINDOW - Duration::from_secs(1)).await;
+        assert!(dedup.is_duplicate(mint, signature));
+
+        tokio::time::advance(WINDOW).await;
+        assert!(!dedup.is_duplicate(mint, signature));
+    }
+}
@@ -0,0 +1,51 @@
+use solana_sdk::pubkey::Pubkey;
+
+/// How pubkeys are rendered in console output. Sinks and the log file always
+/// get the full key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PubkeyStyle {
+    #[default]
+    Full,
+    /// `AbCd…WxYz`
+    Abbrev { prefix: usize, suffix: usize },
+}
+
+impl PubkeyStyle {
+    /// `--pubkey-abbrev`: `N` keeps N characters at each end, `PREFIX:SUFFIX`
+    /// keeps a different number at each.
+    pub fn parse_abbrev(value: &str) -> Result<Self, String> {
+        let parse = |part: &str| {
+            part.trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|len| *len > 0)
+                .ok_or_else(|| {
+                    format!(
+                        "expected N or PREFIX:SUFFIX lengths above 0, got {:?}",
+                        value
+                    )
+                })
+        };
+        let (prefix, suffix) = match value.split_once(':') {
+            Some((prefix, suffix)) => (parse(prefix)?, parse(suffix)?),
+            None => (parse(value)?, parse(value)?),
+        };
+        Ok(PubkeyStyle::Abbrev { prefix, suffix })
+    }
+
+    pub fn show(self, key: &Pubkey) -> String {
+        self.show_str(&key.to_string())
+    }
+
+    /// Abbreviates `value` if it's a base58 pubkey, leaving anything else as is.
+    pub fn show_str(self, value: &str) -> String {
+        match self {
+            PubkeyStyle::Abbrev { prefix, suffix }
+                if prefix + suffix < value.len() && value.parse::<Pubkey>().is_ok() =>
+            {
+                format!("{}…{}", &value[..prefix], &value[value.len() - suffix..])
+            }
+            _ => value.to_string(),
+        }
+    }
+}
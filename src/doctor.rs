@@ -0,0 +1,116 @@
+use std::time::Instant;
+
+use futures_util::stream::StreamExt;
+use tonic::Code;
+use yellowstone_grpc_client::GeyserGrpcClientError;
+
+use solana_pumpfun::stream;
+
+use crate::cli::DoctorArgs;
+
+/// Walks through connect, auth and first update against the configured
+/// endpoint, printing what worked and a hint for what didn't.
+pub async fn run(args: DoctorArgs) -> anyhow::Result<()> {
+    println!(
+        "solana-pumpfun {} ({})",
+        env!("CARGO_PKG_VERSION"),
+        env!("GIT_HASH")
+    );
+    println!("Endpoint: {}", args.connection.endpoint);
+    println!(
+        "x-token: {}",
+        if args.connection.x_token.is_some() {
+            "set"
+        } else {
+            "not set"
+        }
+    );
+
+    let started = Instant::now();
+    let mut client = match args.connection.connect().await {
+        Ok(client) => {
+            println!("[ok]   connect + TLS handshake in {:?}", started.elapsed());
+            client
+        }
+        Err(e) => {
+            println!("[fail] connect: {:#}", e);
+            println!("       hint: {}", connect_hint(&e));
+            anyhow::bail!("endpoint unreachable");
+        }
+    };
+
+    let started = Instant::now();
+    match client.get_version().await {
+        Ok(version) => println!(
+            "[ok]   auth accepted, server version {} in {:?}",
+            version.version,
+            started.elapsed()
+        ),
+        Err(e) => {
+            println!("[fail] GetVersion: {}", e);
+            println!("       hint: {}", status_hint(&e));
+            anyhow::bail!("endpoint rejected requests");
+        }
+    }
+
+    let started = Instant::now();
+    let subscribe_stream =
+        stream::subscribe(&mut client, stream::pumpfun_subscribe_request()).await?;
+    tokio::pin!(subscribe_stream);
+    match tokio::time::timeout(args.timeout, subscribe_stream.next()).await {
+        Ok(Some(Ok(_))) => println!("[ok]   first Pumpfun update after {:?}", started.elapsed()),
+        Ok(Some(Err(status))) => {
+            println!("[fail] subscribe: {}", status);
+            println!(
+                "       hint: {}",
+                status_hint(&GeyserGrpcClientError::TonicStatus(status))
+            );
+            anyhow::bail!("subscription failed");
+        }
+        Ok(None) => {
+            println!("[fail] subscribe: stream closed before the first update");
+            println!("       hint: the endpoint may limit concurrent streams for this token");
+            anyhow::bail!("subscription failed");
+        }
+        Err(_) => {
+            println!("[fail] no update within {:?}", args.timeout);
+            println!(
+                "       hint: the endpoint may not stream transactions, or Pumpfun is idle; retry with a longer --timeout"
+            );
+            anyhow::bail!("subscription idle");
+        }
+    }
+
+    println!("All checks passed");
+    Ok(())
+}
+
+fn connect_hint(error: &anyhow::Error) -> &'static str {
+    let message = format!("{:#}", error).to_lowercase();
+    if message.contains("x-token") {
+        "the x-token contains characters that can't be sent as a header"
+    } else if message.contains("dns") || message.contains("lookup") {
+        "the endpoint host doesn't resolve, check --endpoint"
+    } else if message.contains("certificate") || message.contains("tls") {
+        "TLS failed, pass --ca-cert for a private CA or --client-cert/--client-key for mutual TLS"
+    } else if message.contains("refused") || message.contains("timed out") {
+        "nothing is listening there, check the --endpoint host and port"
+    } else if message.contains("failed to read") {
+        "a --ca-cert/--client-cert/--client-key file can't be read"
+    } else {
+        "check --endpoint, including the https:// scheme and port"
+    }
+}
+
+fn status_hint(error: &GeyserGrpcClientError) -> &'static str {
+    match error {
+        GeyserGrpcClientError::TonicStatus(status) => match status.code() {
+            Code::Unauthenticated | Code::PermissionDenied => "auth rejected, check --x-token",
+            Code::Unimplemented => "the endpoint doesn't speak the Yellowstone Geyser API",
+            Code::ResourceExhausted => "rate limited or over the plan's stream limit",
+            Code::Unavailable => "the endpoint is up but unavailable, retry later",
+            _ => "unexpected gRPC status, see the message above",
+        },
+        _ => "the request could not be sent",
+    }
+}
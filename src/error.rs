@@ -0,0 +1,32 @@
+use thiserror::Error;
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Error)]
+pub enum PumpfunError {
+    #[error("Failed to connect to Geyser: {0}")]
+    Connect(#[source] BoxError),
+    #[error("Failed to subscribe: {0}")]
+    Subscribe(#[source] BoxError),
+    #[error("Failed to decode program data: {0}")]
+    Decode(String),
+    #[error("Data too short for {field}: need {needed} bytes")]
+    Truncated { field: String, needed: usize },
+    #[error("{field} declares {len} bytes, over the {max} byte limit")]
+    FieldTooLong {
+        field: String,
+        len: usize,
+        max: usize,
+    },
+    #[error("Invalid event discriminator: {0:?}")]
+    InvalidDiscriminator([u8; 8]),
+    #[error("Invalid transaction update: {0}")]
+    InvalidUpdate(String),
+}
+
+impl PumpfunError {
+    /// Network failures worth retrying, as opposed to permanent parse failures.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Connect(_) | Self::Subscribe(_))
+    }
+}
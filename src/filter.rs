@@ -0,0 +1,644 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use log::info;
+use solana_pumpfun::parser::CreateTokenInfo;
+use solana_sdk::pubkey::Pubkey;
+use url::Url;
+
+use crate::cli::StreamArgs;
+
+#[derive(Debug, Default)]
+pub struct LaunchFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub creator_allowlist: Option<CreatorList>,
+    pub creator_blocklist: Option<CreatorList>,
+    pub uri_domain_allow: Vec<String>,
+    pub uri_domain_block: Vec<String>,
+    pub require_uri: bool,
+    // in characters, surrounding whitespace aside
+    pub min_name_len: usize,
+    pub min_symbol_len: usize,
+    pub symbol_globs: Option<GlobSet>,
+    pub name_globs: Option<GlobSet>,
+    pub shard: Option<Shard>,
+}
+
+/// `--shard i/n`: this instance's share of the mints, so `n` instances cover
+/// every launch exactly once without coordinating.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    index: u64,
+    count: u64,
+}
+
+impl Shard {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let invalid = || format!("expected i/n with i < n, e.g. 0/4, got {:?}", value);
+        let (index, count) = value.split_once('/').ok_or_else(invalid)?;
+        let index: u64 = index.trim().parse().map_err(|_| invalid())?;
+        let count: u64 = count.trim().parse().map_err(|_| invalid())?;
+        if index >= count {
+            return Err(invalid());
+        }
+        Ok(Self { index, count })
+    }
+
+    pub fn contains(&self, mint: &Pubkey) -> bool {
+        mint_hash(mint) % self.count == self.index
+    }
+}
+
+/// Creator wallets loaded from a newline-delimited pubkey file.
+#[derive(Debug)]
+pub struct CreatorList {
+    path: PathBuf,
+    creators: HashSet<Pubkey>,
+}
+
+impl CreatorList {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+
+        let mut creators = HashSet::new();
+        for (line_number, line) in contents.lines().enumerate() {
+            // blank lines and `#` comments are allowed
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let creator = Pubkey::from_str(line).map_err(|e| {
+                anyhow::anyhow!(
+                    "{}:{}: invalid pubkey {:?}: {}",
+                    path.display(),
+                    line_number + 1,
+                    line,
+                    e
+                )
+            })?;
+            creators.insert(creator);
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            creators,
+        })
+    }
+
+    pub fn contains(&self, creator: &Pubkey) -> bool {
+        self.creators.contains(creator)
+    }
+
+    pub fn len(&self) -> usize {
+        self.creators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.creators.is_empty()
+    }
+}
+
+impl LaunchFilter {
+    pub fn from_args(args: &StreamArgs) -> anyhow::Result<Self> {
+        Ok(Self {
+            since: args.since,
+            until: args.until,
+            creator_allowlist: args
+                .creator_allowlist
+                .as_deref()
+                .map(CreatorList::load)
+                .transpose()?,
+            creator_blocklist: args
+                .creator_blocklist
+                .as_deref()
+                .map(CreatorList::load)
+                .transpose()?,
+            uri_domain_allow: args.uri_domain_allow.clone(),
+            uri_domain_block: args.uri_domain_block.clone(),
+            require_uri: args.require_uri,
+            min_name_len: args.min_name_len.max(usize::from(args.reject_empty)),
+            min_symbol_len: args.min_symbol_len.max(usize::from(args.reject_empty)),
+            symbol_globs: build_globs(&args.symbol_glob, args.glob_case_sensitive)?,
+            name_globs: build_globs(&args.name_glob, args.glob_case_sensitive)?,
+            shard: args.shard,
+        })
+    }
+
+    /// Re-reads the creator lists. Both are swapped in together, so if either
+    /// file is now broken both are kept as they were.
+    pub fn reload_creator_lists(&mut self) -> anyhow::Result<()> {
+        let reload = |list: &Option<CreatorList>| {
+            list.as_ref()
+                .map(|list| CreatorList::load(&list.path))
+                .transpose()
+        };
+        let allowlist = reload(&self.creator_allowlist)?;
+        let blocklist = reload(&self.creator_blocklist)?;
+        self.creator_allowlist = allowlist;
+        self.creator_blocklist = blocklist;
+
+        for list in [&self.creator_allowlist, &self.creator_blocklist]
+            .into_iter()
+            .flatten()
+        {
+            info!(
+                "Reloaded {} creators from {}",
+                list.len(),
+                list.path.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn matches(&self, token_info: &CreateTokenInfo) -> bool {
+        if self
+            .shard
+            .is_some_and(|shard| !shard.contains(&token_info.mint))
+        {
+            return false;
+        }
+
+        // the blocklist wins over the allowlist
+        if self
+            .creator_blocklist
+            .as_ref()
+            .is_some_and(|blocklist| blocklist.contains(&token_info.user))
+        {
+            return false;
+        }
+        if self
+            .creator_allowlist
+            .as_ref()
+            .is_some_and(|allowlist| !allowlist.contains(&token_info.user))
+        {
+            return false;
+        }
+
+        if token_info.name.trim().chars().count() < self.min_name_len
+            || token_info.symbol.trim().chars().count() < self.min_symbol_len
+        {
+            return false;
+        }
+
+        if self
+            .symbol_globs
+            .as_ref()
+            .is_some_and(|globs| !globs.is_match(&token_info.symbol))
+            || self
+                .name_globs
+                .as_ref()
+                .is_some_and(|globs| !globs.is_match(&token_info.name))
+        {
+            return false;
+        }
+
+        if self.require_uri && !is_usable_uri(&token_info.uri) {
+            return false;
+        }
+
+        if !self.uri_domain_allow.is_empty() || !self.uri_domain_block.is_empty() {
+            // a uri without a host is on no domain: never allowed, never blocked
+            let host = uri_host(&token_info.uri);
+            let on = |domains: &[String]| {
+                host.as_deref()
+                    .is_some_and(|host| domains.iter().any(|domain| on_domain(host, domain)))
+            };
+            if on(&self.uri_domain_block) {
+                return false;
+            }
+            if !self.uri_domain_allow.is_empty() && !on(&self.uri_domain_allow) {
+                return false;
+            }
+        }
+
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+
+        // the block time, so a replay or a lagging stream is placed where it
+        // happened; records without a readable time can't be placed at all
+        let Some(launched_at) = token_info.launched_at() else {
+            return false;
+        };
+
+        self.since.is_none_or(|since| launched_at >= since)
+            && self.until.is_none_or(|until| launched_at <= until)
+    }
+
+    /// Whether `--until` has passed and the stream should stop.
+    pub fn is_expired(&self) -> bool {
+        self.until.is_some_and(|until| Utc::now() > until)
+    }
+}
+
+// FNV-1a, which unlike std's `DefaultHasher` is the same across builds and machines
+fn mint_hash(mint: &Pubkey) -> u64 {
+    mint.as_ref()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+fn build_globs(patterns: &[String], case_sensitive: bool) -> anyhow::Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut globs = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Invalid glob {:?}: {}", pattern, e))?;
+        globs.add(glob);
+    }
+    Ok(Some(globs.build()?))
+}
+
+// an http(s) URL with a host, or an `ipfs://` / `ar://` content address
+fn is_usable_uri(uri: &str) -> bool {
+    let Ok(url) = Url::parse(uri.trim()) else {
+        return false;
+    };
+    match url.scheme() {
+        "http" | "https" => url.host_str().is_some_and(|host| host.contains('.')),
+        "ipfs" | "ar" => url.host_str().is_some_and(|cid| !cid.is_empty()),
+        _ => false,
+    }
+}
+
+fn uri_host(uri: &str) -> Option<String> {
+    Url::parse(uri.trim()).ok()?.host_str().map(str::to_string)
+}
+
+// the domain itself or any subdomain, `ipfs.io` covers `gateway.ipfs.io`
+fn on_domain(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|rest| rest.ends_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use clap::Parser;
+
+    use super::*;
+
+    fn time(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 1, hour, 0, 0).unwrap()
+    }
+
+    fn launch() -> CreateTokenInfo {
+        CreateTokenInfo {
+            name: "Moon Cat".to_string(),
+            symbol: "MCAT".to_string(),
+            uri: "https://ipfs.io/ipfs/QmMoonCat".to_string(),
+            mint: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            created_at: "2024-06-01 12:00:00".to_string(),
+            ..Default::default()
+        }
+    }
+
+    // detected at noon, say from a recording, but created at 10:00
+    #[test]
+    fn places_a_launch_by_its_block_time() {
+        let filter = LaunchFilter {
+            since: Some(time(9)),
+            until: Some(time(11)),
+            ..Default::default()
+        };
+        let token_info = CreateTokenInfo {
+            block_time: Some(time(10).timestamp()),
+            ..launch()
+        };
+
+        assert!(filter.matches(&token_info));
+        assert!(!filter.matches(&CreateTokenInfo {
+            block_time: Some(time(8).timestamp()),
+            ..launch()
+        }));
+    }
+
+    #[test]
+    fn places_a_launch_without_block_time_by_detection() {
+        let filter = LaunchFilter {
+            since: Some(time(9)),
+            until: Some(time(11)),
+            ..Default::default()
+        };
+
+        assert!(!filter.matches(&launch()));
+        assert!(!filter.matches(&CreateTokenInfo {
+            created_at: "not a time".to_string(),
+            ..launch()
+        }));
+    }
+
+    fn globs(patterns: &[&str], case_sensitive: bool) -> Option<GlobSet> {
+        let patterns: Vec<_> = patterns.iter().map(|p| p.to_string()).collect();
+        build_globs(&patterns, case_sensitive).unwrap()
+    }
+
+    #[test]
+    fn matches_symbol_globs_in_any_case() {
+        let filter = LaunchFilter {
+            symbol_globs: globs(&["moon*", "*INU"], false),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&CreateTokenInfo {
+            symbol: "MOONCAT".to_string(),
+            ..launch()
+        }));
+        assert!(filter.matches(&CreateTokenInfo {
+            symbol: "shibinu".to_string(),
+            ..launch()
+        }));
+        assert!(!filter.matches(&launch()));
+    }
+
+    #[test]
+    fn matches_globs_case_sensitively_when_asked() {
+        let filter = LaunchFilter {
+            symbol_globs: globs(&["MOON*"], true),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&CreateTokenInfo {
+            symbol: "MOONCAT".to_string(),
+            ..launch()
+        }));
+        assert!(!filter.matches(&CreateTokenInfo {
+            symbol: "MoonCat".to_string(),
+            ..launch()
+        }));
+    }
+
+    #[test]
+    fn matches_name_globs() {
+        let filter = LaunchFilter {
+            name_globs: globs(&["* Cat"], false),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&launch()));
+        assert!(!filter.matches(&CreateTokenInfo {
+            name: "Moon Dog".to_string(),
+            ..launch()
+        }));
+    }
+
+    #[test]
+    fn rejects_an_invalid_glob() {
+        assert!(build_globs(&["MOON[".to_string()], false).is_err());
+        assert!(build_globs(&[], false).unwrap().is_none());
+    }
+
+    #[test]
+    fn parses_shards() {
+        assert_eq!(Shard::parse("1/4"), Ok(Shard { index: 1, count: 4 }));
+        assert_eq!(Shard::parse(" 0 / 1 "), Ok(Shard { index: 0, count: 1 }));
+        for invalid in ["4/4", "0/0", "1", "-1/4", "a/b", "1/4/8"] {
+            assert!(Shard::parse(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn puts_each_mint_in_exactly_one_shard() {
+        let count = 4;
+        let shards: Vec<_> = (0..count)
+            .map(|index| Shard::parse(&format!("{}/{}", index, count)).unwrap())
+            .collect();
+
+        let mut per_shard = vec![0; count];
+        for _ in 0..1000 {
+            let mint = Pubkey::new_unique();
+            let owners: Vec<_> = (0..count).filter(|&i| shards[i].contains(&mint)).collect();
+            assert_eq!(owners.len(), 1);
+            per_shard[owners[0]] += 1;
+        }
+        // the hash spreads even sequential mints over every shard
+        assert!(
+            per_shard.iter().all(|&launches| launches > 150),
+            "{:?}",
+            per_shard
+        );
+    }
+
+    fn stream_args(flags: &[&str]) -> StreamArgs {
+        let args = ["solana-pumpfun", "stream"].iter().chain(flags);
+        match crate::cli::Cli::try_parse_from(args).unwrap().command {
+            crate::cli::Commands::Stream(args) => args,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn measures_names_and_symbols_in_trimmed_characters() {
+        let filter = LaunchFilter {
+            min_name_len: 4,
+            min_symbol_len: 3,
+            ..Default::default()
+        };
+
+        // three characters, six bytes
+        assert!(!filter.matches(&CreateTokenInfo {
+            name: "  猫猫猫  ".to_string(),
+            ..launch()
+        }));
+        assert!(filter.matches(&CreateTokenInfo {
+            name: "猫猫猫猫".to_string(),
+            ..launch()
+        }));
+        assert!(!filter.matches(&CreateTokenInfo {
+            symbol: " MC ".to_string(),
+            ..launch()
+        }));
+    }
+
+    #[test]
+    fn rejects_blank_names_and_symbols_with_reject_empty() {
+        let filter = LaunchFilter::from_args(&stream_args(&["--reject-empty"])).unwrap();
+
+        assert!(filter.matches(&launch()));
+        assert!(!filter.matches(&CreateTokenInfo {
+            name: " ".to_string(),
+            ..launch()
+        }));
+        assert!(!filter.matches(&CreateTokenInfo {
+            symbol: String::new(),
+            ..launch()
+        }));
+
+        // a longer minimum still applies
+        let filter =
+            LaunchFilter::from_args(&stream_args(&["--reject-empty", "--min-name-len", "9"]))
+                .unwrap();
+        assert!(!filter.matches(&launch()));
+    }
+
+    fn with_uri(uri: &str) -> CreateTokenInfo {
+        CreateTokenInfo {
+            uri: uri.to_string(),
+            ..launch()
+        }
+    }
+
+    #[test]
+    fn allows_a_domain_and_its_subdomains() {
+        let filter = LaunchFilter {
+            uri_domain_allow: vec!["ipfs.io".to_string()],
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&with_uri("https://ipfs.io/ipfs/QmMoonCat")));
+        assert!(filter.matches(&with_uri("https://gateway.ipfs.io/ipfs/QmMoonCat")));
+        assert!(!filter.matches(&with_uri("https://evilipfs.io/ipfs/QmMoonCat")));
+        assert!(!filter.matches(&with_uri("https://ipfs.io.evil.com/ipfs/QmMoonCat")));
+        // on no domain at all
+        assert!(!filter.matches(&with_uri("not a uri")));
+        assert!(!filter.matches(&with_uri("")));
+    }
+
+    #[test]
+    fn blocks_a_domain_even_when_allowed() {
+        let filter = LaunchFilter {
+            uri_domain_allow: vec!["ipfs.io".to_string()],
+            uri_domain_block: vec!["gateway.ipfs.io".to_string()],
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&with_uri("https://ipfs.io/ipfs/QmMoonCat")));
+        assert!(!filter.matches(&with_uri("https://gateway.ipfs.io/ipfs/QmMoonCat")));
+
+        let block_only = LaunchFilter {
+            uri_domain_block: vec!["ipfs.io".to_string()],
+            ..Default::default()
+        };
+        assert!(!block_only.matches(&launch()));
+        assert!(block_only.matches(&with_uri("https://arweave.net/MoonCat")));
+        assert!(block_only.matches(&with_uri("")));
+    }
+
+    #[test]
+    fn accepts_only_usable_uris() {
+        for uri in [
+            "https://ipfs.io/ipfs/QmMoonCat",
+            "  https://arweave.net/MoonCat  ",
+            "ipfs://QmMoonCat",
+            "ar://MoonCat",
+        ] {
+            assert!(is_usable_uri(uri), "{:?}", uri);
+        }
+        for uri in [
+            "",
+            "   ",
+            "http://localhost/metadata.json",
+            "ipfs://",
+            "ar://",
+            "ftp://ipfs.io/ipfs/QmMoonCat",
+            "data:application/json,{}",
+        ] {
+            assert!(!is_usable_uri(uri), "{:?}", uri);
+        }
+    }
+
+    #[test]
+    fn requires_a_usable_uri_when_asked() {
+        let filter = LaunchFilter {
+            require_uri: true,
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&launch()));
+        assert!(!filter.matches(&with_uri(" ")));
+    }
+
+    fn creator_list(dir: &Path, name: &str, contents: &str) -> CreatorList {
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        CreatorList::load(&path).unwrap()
+    }
+
+    #[test]
+    fn loads_a_creator_list_around_comments_and_blank_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let list = creator_list(
+            dir.path(),
+            "creators.txt",
+            &format!("# known ruggers\n\n{}\n  {}  # again\n", first, second),
+        );
+
+        assert_eq!(list.len(), 2);
+        assert!(list.contains(&first) && list.contains(&second));
+
+        let path = dir.path().join("broken.txt");
+        fs::write(&path, format!("{}\nnot-a-pubkey\n", first)).unwrap();
+        let err = CreatorList::load(&path).unwrap_err().to_string();
+        assert!(err.contains("broken.txt:2"), "{}", err);
+    }
+
+    #[test]
+    fn blocks_a_creator_even_when_allowlisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let token_info = launch();
+        let (allowed, blocked) = (
+            format!("{}\n{}\n", token_info.user, Pubkey::new_unique()),
+            format!("{}\n", token_info.user),
+        );
+        let filter = LaunchFilter {
+            creator_allowlist: Some(creator_list(dir.path(), "allow.txt", &allowed)),
+            ..Default::default()
+        };
+        assert!(filter.matches(&token_info));
+        assert!(!filter.matches(&launch()));
+
+        let filter = LaunchFilter {
+            creator_blocklist: Some(creator_list(dir.path(), "block.txt", &blocked)),
+            ..filter
+        };
+        assert!(!filter.matches(&token_info));
+    }
+
+    #[test]
+    fn keeps_both_creator_lists_when_one_fails_to_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let (creator, added) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut filter = LaunchFilter {
+            creator_allowlist: Some(creator_list(dir.path(), "allow.txt", &creator.to_string())),
+            creator_blocklist: Some(creator_list(dir.path(), "block.txt", "")),
+            ..Default::default()
+        };
+
+        fs::write(
+            dir.path().join("allow.txt"),
+            format!("{}\n{}", creator, added),
+        )
+        .unwrap();
+        fs::write(dir.path().join("block.txt"), "not-a-pubkey").unwrap();
+        assert!(filter.reload_creator_lists().is_err());
+        assert_eq!(filter.creator_allowlist.as_ref().unwrap().len(), 1);
+
+        fs::write(dir.path().join("block.txt"), creator.to_string()).unwrap();
+        filter.reload_creator_lists().unwrap();
+        assert_eq!(filter.creator_allowlist.as_ref().unwrap().len(), 2);
+        assert!(
+            filter
+                .creator_blocklist
+                .as_ref()
+                .unwrap()
+                .contains(&creator)
+        );
+    }
+}
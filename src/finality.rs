@@ -0,0 +1,57 @@
+//! Confirms that launches captured below finalized commitment made it onto the
+//! finalized fork, so forked-away transactions don't linger as phantom tokens.
+
+use std::time::Duration;
+
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use tokio::time::Instant;
+
+// finalization takes ~32 slots, no point asking much more often than that moves
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct FinalityChecker {
+    rpc: RpcClient,
+    timeout: Duration,
+}
+
+impl FinalityChecker {
+    pub fn new(rpc_url: &str, timeout: Duration) -> Self {
+        Self {
+            rpc: RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::finalized()),
+            timeout,
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Whether `signature` is finalized, without error, within the timeout.
+    pub async fn wait_finalized(&self, signature: &Signature) -> anyhow::Result<bool> {
+        let deadline = Instant::now() + self.timeout;
+
+        loop {
+            let status = self
+                .rpc
+                .get_signature_statuses(&[*signature])
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to get status of {}: {}", signature, e))?
+                .value
+                .into_iter()
+                .next()
+                .flatten();
+
+            if let Some(status) = status
+                && status.satisfies_commitment(CommitmentConfig::finalized())
+            {
+                return Ok(status.err.is_none());
+            }
+            if Instant::now() + POLL_INTERVAL > deadline {
+                return Ok(false);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
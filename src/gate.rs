@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use solana_pumpfun::parser::CreateTokenInfo;
+use solana_sdk::pubkey::Pubkey;
+use tokio::time::Instant;
+use tracing::Span;
+
+use crate::cli::StreamArgs;
+
+struct HeldLaunch {
+    token_info: CreateTokenInfo,
+    span: Span,
+    held_at: Instant,
+    traded: bool,
+}
+
+/// Holds launches back from the log file and sinks until `--require-first-trade`
+/// and `--min-launches-to-write` are met, dropping those still held after
+/// `--gate-timeout`.
+pub struct LaunchGate {
+    require_first_trade: bool,
+    min_creator_launches: usize,
+    timeout: Duration,
+    held: HashMap<Pubkey, HeldLaunch>,
+    // mints still in enrichment, and whether they traded meanwhile
+    watched: HashMap<Pubkey, (Instant, bool)>,
+    // launches seen per creator since it last had none held, held or not
+    creator_launches: HashMap<Pubkey, usize>,
+}
+
+impl LaunchGate {
+    /// None unless one of the gate flags is set.
+    pub fn from_args(args: &StreamArgs) -> Option<Self> {
+        let min_creator_launches = args.min_launches_to_write.unwrap_or(1);
+        if !args.require_first_trade && min_creator_launches <= 1 {
+            return None;
+        }
+
+        Some(Self {
+            require_first_trade: args.require_first_trade,
+            min_creator_launches,
+            timeout: args.gate_timeout,
+            held: HashMap::new(),
+            watched: HashMap::new(),
+            creator_launches: HashMap::new(),
+        })
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Starts counting trades for a launch that reaches `admit` later, once
+    /// its enrichment finishes.
+    pub fn watch(&mut self, mint: Pubkey) {
+        self.watched.insert(mint, (Instant::now(), false));
+    }
+
+    /// Takes a finished launch, returning the launches it makes ready: itself,
+    /// or a creator's earlier launches once it reaches `--min-launches-to-write`.
+    pub fn admit(&mut self, token_info: &CreateTokenInfo) -> Vec<(CreateTokenInfo, Span)> {
+        let creator = token_info.user;
+        *self.creator_launches.entry(creator).or_default() += 1;
+
+        self.held.insert(
+            token_info.mint,
+            HeldLaunch {
+                token_info: token_info.clone(),
+                span: Span::current(),
+                held_at: Instant::now(),
+                traded: self
+                    .watched
+                    .remove(&token_info.mint)
+                    .is_some_and(|(_, traded)| traded),
+            },
+        );
+        self.release(|held| held.token_info.user == creator)
+    }
+
+    /// Records a trade, releasing its mint if it was only waiting on one.
+    pub fn observe_trade(&mut self, mint: &Pubkey) -> Vec<(CreateTokenInfo, Span)> {
+        if let Some((_, traded)) = self.watched.get_mut(mint) {
+            *traded = true;
+        }
+        let Some(held) = self.held.get_mut(mint) else {
+            return vec![];
+        };
+        held.traded = true;
+        self.release(|held| held.token_info.mint == *mint)
+    }
+
+    /// Drops the launches held longer than the timeout, returning them.
+    pub fn expire(&mut self) -> Vec<CreateTokenInfo> {
+        let timeout = self.timeout;
+        // enrichment gave up on these, or dropped them
+        self.watched
+            .retain(|_, (watched_at, _)| watched_at.elapsed() < timeout * 2);
+        let expired: Vec<Pubkey> = self
+            .held
+            .iter()
+            .filter(|(_, held)| held.held_at.elapsed() >= timeout)
+            .map(|(mint, _)| *mint)
+            .collect();
+        let expired: Vec<CreateTokenInfo> = expired
+            .into_iter()
+            .filter_map(|mint| self.held.remove(&mint))
+            .map(|held| held.token_info)
+            .collect();
+        self.forget_creators(expired.iter());
+        expired
+    }
+
+    pub fn held(&self) -> usize {
+        self.held.len()
+    }
+
+    fn release(&mut self, affected: impl Fn(&HeldLaunch) -> bool) -> Vec<(CreateTokenInfo, Span)> {
+        let mut ready: Vec<(CreateTokenInfo, Span)> = vec![];
+        for mint in self
+            .held
+            .iter()
+            .filter(|(_, held)| affected(held) && self.is_ready(held))
+            .map(|(mint, _)| *mint)
+            .collect::<Vec<_>>()
+        {
+            if let Some(held) = self.held.remove(&mint) {
+                ready.push((held.token_info, held.span));
+            }
+        }
+        // a creator's earlier launches go out ahead of the one that released them
+        ready.sort_by_key(|(token_info, _)| (token_info.slot, token_info.tx_index));
+        self.forget_creators(ready.iter().map(|(token_info, _)| token_info));
+        ready
+    }
+
+    // counts are only kept for creators with a launch held, or they'd grow all session
+    fn forget_creators<'a>(&mut self, gone: impl Iterator<Item = &'a CreateTokenInfo>) {
+        for token_info in gone {
+            let creator = token_info.user;
+            if !self
+                .held
+                .values()
+                .any(|held| held.token_info.user == creator)
+            {
+                self.creator_launches.remove(&creator);
+            }
+        }
+    }
+
+    fn is_ready(&self, held: &HeldLaunch) -> bool {
+        let creator_launches = self
+            .creator_launches
+            .get(&held.token_info.user)
+            .copied()
+            .unwrap_or_default();
+        (!self.require_first_trade || held.traded) && creator_launches >= self.min_creator_launches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    fn gate(require_first_trade: bool, min_creator_launches: usize) -> LaunchGate {
+        LaunchGate {
+            require_first_trade,
+            min_creator_launches,
+            timeout: TIMEOUT,
+            held: HashMap::new(),
+            watched: HashMap::new(),
+            creator_launches: HashMap::new(),
+        }
+    }
+
+    fn launch(creator: Pubkey, slot: u64, tx_index: u64) -> CreateTokenInfo {
+        CreateTokenInfo {
+            mint: Pubkey::new_unique(),
+            user: creator,
+            slot,
+            tx_index,
+            ..Default::default()
+        }
+    }
+
+    fn mints(released: &[(CreateTokenInfo, Span)]) -> Vec<Pubkey> {
+        released
+            .iter()
+            .map(|(token_info, _)| token_info.mint)
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn releases_a_launch_on_its_first_trade() {
+        let mut gate = gate(true, 1);
+        let token_info = launch(Pubkey::new_unique(), 10, 0);
+
+        assert!(gate.admit(&token_info).is_empty());
+        assert!(gate.observe_trade(&Pubkey::new_unique()).is_empty());
+        assert_eq!(
+            mints(&gate.observe_trade(&token_info.mint)),
+            [token_info.mint]
+        );
+        assert_eq!(gate.held(), 0);
+        assert!(gate.creator_launches.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn counts_a_trade_seen_during_enrichment() {
+        let mut gate = gate(true, 1);
+        let token_info = launch(Pubkey::new_unique(), 10, 0);
+
+        gate.watch(token_info.mint);
+        assert!(gate.observe_trade(&token_info.mint).is_empty());
+        assert_eq!(mints(&gate.admit(&token_info)), [token_info.mint]);
+        assert!(gate.watched.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn releases_a_creators_launches_in_order_at_the_minimum() {
+        let mut gate = gate(false, 3);
+        let creator = Pubkey::new_unique();
+        let (third, first, second) = (
+            launch(creator, 12, 0),
+            launch(creator, 10, 4),
+            launch(creator, 10, 9),
+        );
+
+        assert!(gate.admit(&third).is_empty());
+        assert!(gate.admit(&first).is_empty());
+        // another creator's launches count for it alone
+        assert!(gate.admit(&launch(Pubkey::new_unique(), 11, 0)).is_empty());
+        assert_eq!(
+            mints(&gate.admit(&second)),
+            [first.mint, second.mint, third.mint]
+        );
+        assert_eq!(gate.held(), 1);
+        assert!(!gate.creator_launches.contains_key(&creator));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drops_launches_held_past_the_timeout() {
+        let mut gate = gate(true, 1);
+        let token_info = launch(Pubkey::new_unique(), 10, 0);
+        gate.admit(&token_info);
+
+        tokio::time::advance(TIMEOUT - Duration::from_secs(1)).await;
+        assert!(gate.expire().is_empty());
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        let expired: Vec<_> = gate.expire().into_iter().map(|t| t.mint).collect();
+        assert_eq!(expired, [token_info.mint]);
+        assert_eq!(gate.held(), 0);
+        assert!(gate.creator_launches.is_empty());
+        // too late to be released
+        assert!(gate.observe_trade(&token_info.mint).is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+// /healthz turns unready when the stream has been silent this long
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Liveness counters shared between the stream loop and the health server.
+#[derive(Debug)]
+pub struct HealthState {
+    started_at: Instant,
+    connected: AtomicBool,
+    // ms since `started_at` of the last update, 0 until the first one
+    last_message_ms: AtomicU64,
+    reconnects: AtomicU64,
+    launches: AtomicU64,
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            connected: AtomicBool::new(false),
+            last_message_ms: AtomicU64::new(0),
+            reconnects: AtomicU64::new(0),
+            launches: AtomicU64::new(0),
+        }
+    }
+}
+
+impl HealthState {
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn record_message(&self) {
+        let elapsed_ms = self.started_at.elapsed().as_millis() as u64;
+        self.last_message_ms
+            .store(elapsed_ms.max(1), Ordering::Relaxed);
+    }
+
+    pub fn record_reconnect(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    pub fn set_launches(&self, launches: u64) {
+        self.launches.store(launches, Ordering::Relaxed);
+    }
+
+    fn last_message_age(&self) -> Option<Duration> {
+        match self.last_message_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(
+                self.started_at
+                    .elapsed()
+                    .saturating_sub(Duration::from_millis(ms)),
+            ),
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+            && self
+                .last_message_age()
+                .is_some_and(|age| age <= STALE_AFTER)
+    }
+
+    fn status_json(&self) -> serde_json::Value {
+        json!({
+            "ready": self.is_ready(),
+            "connected": self.connected.load(Ordering::Relaxed),
+            "uptime_secs": self.started_at.elapsed().as_secs(),
+            "last_message_age_ms": self.last_message_age().map(|age| age.as_millis() as u64),
+            "reconnects": self.reconnects.load(Ordering::Relaxed),
+            "launches": self.launches.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// Serves `GET /healthz` and `GET /status` on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr, state: Arc<HealthState>) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind health server on {}: {}", addr, e))?;
+    info!("Health endpoint listening on http://{}", addr);
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((socket, _)) => {
+                    let state = state.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = respond(socket, &state).await {
+                            warn!("Health request failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => warn!("Health server accept failed: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+async fn respond(mut socket: TcpStream, state: &HealthState) -> std::io::Result<()> {
+    let mut buf = [0u8; 1024];
+    let n = socket.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let mut request_line = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let (method, path) = (request_line.next(), request_line.next());
+
+    let (status, content_type, body) = match (method, path) {
+        (Some("GET"), Some("/healthz")) if state.is_ready() => {
+            ("200 OK", "text/plain", "ok\n".to_string())
+        }
+        (Some("GET"), Some("/healthz")) => (
+            "503 Service Unavailable",
+            "text/plain",
+            "unavailable\n".to_string(),
+        ),
+        (Some("GET"), Some("/status")) => (
+            "200 OK",
+            "application/json",
+            state.status_json().to_string(),
+        ),
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await?;
+    socket.shutdown().await
+}
@@ -0,0 +1,24 @@
+#[cfg(feature = "parse")]
+pub mod curve;
+#[cfg(feature = "parse")]
+pub mod error;
+#[cfg(feature = "stream")]
+pub mod finality;
+#[cfg(feature = "stream")]
+pub mod metadata;
+#[cfg(feature = "parse")]
+pub mod parser;
+#[cfg(feature = "stream")]
+pub mod price;
+#[cfg(feature = "parse")]
+pub mod records;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "stream")]
+pub mod trade;
+#[cfg(feature = "stream")]
+pub mod utils;
+#[cfg(feature = "stream")]
+pub mod verify;
+#[cfg(feature = "stream")]
+pub mod wallet;
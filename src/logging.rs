@@ -0,0 +1,146 @@
+use std::fs::OpenOptions;
+use std::io::IsTerminal;
+use std::sync::Mutex;
+
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{Layer, Registry};
+
+use crate::cli::Cli;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// human-readable lines
+    #[default]
+    Text,
+    /// one JSON object per line, for log aggregators
+    Json,
+}
+
+/// Keeps the OTLP exporter alive, flushing spans still buffered on drop.
+#[derive(Default)]
+pub struct LoggingGuard {
+    #[cfg(feature = "otel")]
+    tracer_provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+}
+
+impl Drop for LoggingGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(tracer_provider) = self.tracer_provider.take()
+            && let Err(e) = tracer_provider.shutdown()
+        {
+            eprintln!("Failed to flush OTLP spans: {}", e);
+        }
+    }
+}
+
+/// Operational logs go to stderr at `--log-level` and, with `--log-file`, to a
+/// file at `--log-file-level`. The file layer never gets ANSI escapes, whatever
+/// the console does. `--log-format` applies to both. With `--otel-endpoint`, this crate's spans are also
+/// exported over OTLP.
+pub fn init(cli: &Cli) -> anyhow::Result<LoggingGuard> {
+    #[allow(unused_mut)]
+    let mut guard = LoggingGuard::default();
+    let mut layers = vec![fmt_layer(
+        cli.log_format,
+        std::io::stderr,
+        std::io::stderr().is_terminal(),
+        cli.log_level,
+    )];
+
+    if let Some(path) = &cli.log_file {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open log file {}: {}", path, e))?;
+        layers.push(fmt_layer(
+            cli.log_format,
+            Mutex::new(file),
+            false,
+            cli.log_file_level,
+        ));
+    }
+
+    #[cfg(feature = "otel")]
+    if let Some(endpoint) = &cli.otel_endpoint {
+        let (tracer_provider, layer) = otel_layer(endpoint)?;
+        guard.tracer_provider = Some(tracer_provider);
+        layers.push(layer.boxed());
+    }
+
+    tracing_subscriber::registry()
+        .with(layers)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!("Failed to initialize logging: {}", e))?;
+
+    Ok(guard)
+}
+
+#[cfg(feature = "otel")]
+fn otel_layer<S>(
+    endpoint: &str,
+) -> anyhow::Result<(
+    opentelemetry_sdk::trace::TracerProvider,
+    impl tracing_subscriber::Layer<S>,
+)>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::KeyValue;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::WithExportConfig;
+    use tracing_subscriber::filter::Targets;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to build OTLP exporter for {}: {}", endpoint, e))?;
+    let tracer_provider = opentelemetry_sdk::trace::TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(opentelemetry_sdk::Resource::new([KeyValue::new(
+            "service.name",
+            env!("CARGO_PKG_NAME"),
+        )]))
+        .build();
+    let tracer = tracer_provider.tracer(env!("CARGO_PKG_NAME"));
+
+    // only our own spans, exporting the exporter's gRPC spans would feed back on itself
+    let layer = tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .with_filter(Targets::new().with_target("solana_pumpfun", tracing::Level::INFO));
+
+    Ok((tracer_provider, layer))
+}
+
+// `--log-format json` is one object per line: timestamp, level, target,
+// fields.message and the enclosing spans' fields (slot, mint, ...)
+fn fmt_layer<W>(
+    format: LogFormat,
+    writer: W,
+    ansi: bool,
+    level: LevelFilter,
+) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer);
+    match format {
+        LogFormat::Text => layer.with_ansi(ansi).with_filter(level).boxed(),
+        LogFormat::Json => layer
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_filter(level)
+            .boxed(),
+    }
+}
+
+pub fn parse_level(value: &str) -> Result<LevelFilter, String> {
+    value
+        .parse()
+        .map_err(|_| format!("unknown log level {:?}", value))
+}
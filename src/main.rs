@@ -1,217 +1,280 @@
+pub mod aggregate;
+pub mod backfill;
+pub mod buyers;
 pub mod cli;
-pub mod utils;
+pub mod control;
+pub mod copycat;
+pub mod cursor;
+pub mod dedup;
+pub mod display;
+pub mod doctor;
+pub mod filter;
+pub mod gate;
+pub mod health;
+pub mod logging;
+pub mod pipeline;
+pub mod projection;
+pub mod recording;
+pub mod reuse;
+pub mod sampler;
+pub mod sinks;
+pub mod sources;
+pub mod stats;
+pub mod surge;
+pub mod tail;
+pub mod template;
+pub mod transform;
+
+use std::sync::Arc;
 
-use futures_util::stream::StreamExt;
 use log::error;
-use solana_sdk::{pubkey, pubkey::Pubkey};
-use solana_transaction_status::option_serializer::OptionSerializer;
 
+use chrono::Utc;
 use clap::Parser;
 use tokio::sync::mpsc;
-use tonic::transport::ClientTlsConfig;
-use yellowstone_grpc_client::GeyserGrpcClient;
-use yellowstone_grpc_proto::prelude::{
-    SubscribeRequest, SubscribeRequestFilterTransactions, SubscribeUpdate,
-    subscribe_update::UpdateOneof,
-};
-
-use crate::cli::{Cli, Commands};
-use crate::utils::{
-    CreateTokenInfo, TransactionPretty, append_to_json_file, parse_create_token_data,
-};
-
-// static DEFAULT_GEYSER_ENDPOINT: &str = "https://solana-yellowstone-grpc.publicnode.com:443";
-static DEFAULT_GEYSER_ENDPOINT: &str = "https://printworld.shyft.to";
-const PUMPFUN_PROGRAM_ID: Pubkey = pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+use yellowstone_grpc_proto::prelude::SubscribeUpdate;
+use yellowstone_grpc_proto::prelude::subscribe_update::UpdateOneof;
+
+use solana_pumpfun::stream;
+use solana_pumpfun::utils::read_records;
+
+use crate::cli::{Cli, Commands, SourceKind, StreamArgs};
+use crate::control::{ControlEvent, GapTracker};
+use crate::cursor::Cursor;
+use crate::health::HealthState;
+use crate::pipeline::Pipeline;
+use crate::recording::Recorder;
+use crate::sources::geyser::GeyserSource;
+#[cfg(feature = "kafka")]
+use crate::sources::kafka::KafkaSource;
+use crate::sources::recording::RecordingSource;
+use crate::sources::{TransactionSource, spawn_source};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    // held until exit so buffered spans are exported
+    let _logging = logging::init(&cli)?;
 
     match cli.command {
-        Commands::Stream => {
-            stream_pumpfun_launches().await?;
+        Commands::Stream(args) => {
+            stream_pumpfun_launches(args).await?;
+        }
+        Commands::Backfill(args) => {
+            backfill::backfill_pumpfun_launches(args).await?;
+        }
+        Commands::Doctor(args) => {
+            doctor::run(args).await?;
+        }
+        Commands::Replay(args) => {
+            for token_info in read_records(&args.path, args.format)? {
+                println!("{}", serde_json::to_string(&token_info)?);
+            }
         }
     }
 
     Ok(())
 }
 
-async fn stream_pumpfun_launches() -> anyhow::Result<()> {
-    let (tx, mut rx) = mpsc::channel::<SubscribeUpdate>(100);
+async fn stream_pumpfun_launches(args: StreamArgs) -> anyhow::Result<()> {
+    // a replay would buy launches long past
+    if args.auto_buy && (args.tail.is_some() || args.source != SourceKind::Geyser) {
+        anyhow::bail!(
+            "--auto-buy only runs on the live --source geyser stream, not --tail or other sources"
+        );
+    }
+    if let Some(path) = args.tail.clone() {
+        return tail::tail_launches(args, path).await;
+    }
 
-    let mut client = GeyserGrpcClient::build_from_static(DEFAULT_GEYSER_ENDPOINT)
-        .tls_config(ClientTlsConfig::new().with_native_roots())?
-        .connect()
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to connect to Geyser: {:?}", e))?;
-
-    println!("Connected to Geyser at {}", DEFAULT_GEYSER_ENDPOINT);
-
-    let mut subscribe_request = SubscribeRequest::default();
-    subscribe_request.transactions.insert(
-        "pumpfun".to_string(),
-        SubscribeRequestFilterTransactions {
-            vote: Some(false),
-            failed: Some(false),
-            signature: None,
-            account_include: vec![PUMPFUN_PROGRAM_ID.to_string()],
-            account_exclude: vec![],
-            account_required: vec![],
-        },
-    );
-
-    tokio::spawn(async move {
-        let (mut _subscribe_tx, subscribe_stream) =
-            match client.subscribe_with_request(Some(subscribe_request)).await {
-                Ok((tx, stream)) => (tx, stream),
-                Err(e) => {
-                    error!("Failed to subscribe: {:?}", e);
-                    return;
-                }
-            };
+    let pipeline = Pipeline::from_args(&args)?;
 
-        // process stream
-        tokio::pin!(subscribe_stream);
-        while let Some(message) = subscribe_stream.next().await {
-            match message {
-                Ok(update) => {
-                    if let Err(e) = tx.send(update).await {
-                        error!("Failed to send update: {:?}", e);
-                        break;
-                    }
-                }
-                Err(e) => {
-                    error!("Stream error: {:?}", e);
-                    continue;
-                }
+    let health = Arc::new(HealthState::default());
+    if let Some(health_addr) = args.health_addr {
+        health::serve(health_addr, health.clone()).await?;
+    }
+
+    match args.source {
+        SourceKind::Geyser => {
+            let client = args.connection.connect().await?;
+            println!("Connected to Geyser at {}", args.connection.endpoint);
+
+            let subscribe_request = if args.trades_for.is_empty() {
+                stream::programs_subscribe_request(&args.program_ids)
+            } else {
+                stream::trades_subscribe_request(&args.trades_for)
+            };
+            let mut subscribe_request = stream::with_commitment(subscribe_request, args.commitment);
+            if args.auto_resume {
+                subscribe_request.from_slot = resume_slot(&args)?;
             }
+            let source = GeyserSource::new(client, subscribe_request, args.max_reconnects);
+            stream_from_source(&args, pipeline, source, health).await
         }
-    });
-
-    // updates
-    while let Some(msg) = rx.recv().await {
-        if let Some(UpdateOneof::Transaction(subscribe_update_tx)) = msg.update_oneof {
-            if let Err(e) = process_tx_update(TransactionPretty::from(subscribe_update_tx)).await {
-                error!("Error processing account update: {:?}", e);
-                continue;
+        #[cfg(feature = "kafka")]
+        SourceKind::Kafka => {
+            if args.auto_resume {
+                log::warn!(
+                    "--auto-resume has no effect on Kafka, the consumer group keeps its offsets"
+                );
             }
+            let source = KafkaSource::new(&args.kafka)?;
+            println!("Reading updates from Kafka topic {}", source.topic());
+            stream_from_source(&args, pipeline, source, health).await
+        }
+        SourceKind::Recording => {
+            // clap requires it with `--source recording`
+            let path = args.recording.clone().unwrap_or_default();
+            println!("Playing back {}", path.display());
+            stream_from_source(&args, pipeline, RecordingSource::new(&path), health).await
         }
     }
-
-    Ok(())
 }
 
-async fn process_tx_update(transaction_pretty: TransactionPretty) -> anyhow::Result<()> {
-    let trade_raw = transaction_pretty.tx;
-    let meta = trade_raw
-        .meta
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Missing transaction metadata"))?;
-
-    if meta.err.is_some() {
-        return Ok(());
-    }
-
-    let logs = if let OptionSerializer::Some(logs) = &meta.log_messages {
-        logs
-    } else {
-        &vec![]
+// the cursor slot itself is replayed, it may have been cut short
+fn resume_slot(args: &StreamArgs) -> anyhow::Result<Option<u64>> {
+    let Some(path) = &args.cursor_file else {
+        return Ok(None);
     };
+    let slot = Cursor::read(path)?;
+    match slot {
+        Some(slot) => println!("Resuming from slot {} ({})", slot, path.display()),
+        None => println!("No cursor in {} yet, starting live", path.display()),
+    }
+    Ok(slot)
+}
 
-    let instructions = parse_instruction(logs)?;
+/// Runs `source`'s updates through `pipeline` until it ends, Ctrl-C or `--until`.
+async fn stream_from_source<S: TransactionSource>(
+    args: &StreamArgs,
+    mut pipeline: Pipeline,
+    source: S,
+    health: Arc<HealthState>,
+) -> anyhow::Result<()> {
+    let recorder = args.record.as_deref().map(Recorder::create).transpose()?;
+    let (tx, mut rx) = mpsc::channel::<SubscribeUpdate>(100);
+    let source_name = source.name();
+    let source_finite = source.is_finite();
+    let source = spawn_source(source, tx, health.clone());
+    pipeline.send_control(ControlEvent::StreamStarted { at: Utc::now() });
+    let mut gaps = GapTracker::default();
 
-    for token_info in instructions {
-        // print to console
+    if !args.trades_for.is_empty() {
         println!(
-            "New Pumpfun Launch:\n\
-        Token Address: {}\n\
-        Bonding Curve Address: {}\n\
-        Name: {}\n\
-        Symbol: {}\n\
-        Owner: {}\n\
-        Slot: {}\n\
-        ",
-            token_info.mint,
-            token_info.bonding_curve,
-            token_info.name,
-            token_info.symbol,
-            token_info.user,
-            transaction_pretty.slot
+            "Following trades for {}",
+            args.trades_for
+                .iter()
+                .map(|mint| mint.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
         );
-
-        append_to_json_file(&token_info)?;
-        println!("---");
     }
 
-    Ok(())
-}
+    let mut reload_signal =
+        ReloadSignal::new(args.creator_allowlist.is_some() || args.creator_blocklist.is_some());
+    // cleared when the loop stops for Ctrl-C or `--until` rather than the source ending
+    let mut source_ended = true;
 
-pub fn parse_instruction(logs: &[String]) -> anyhow::Result<Vec<CreateTokenInfo>> {
-    let mut current_instruction = None;
-    let mut program_data = String::new();
-    let mut invoke_depth = 0;
-    let mut last_data_len = 0;
-
-    let mut instructions: Vec<CreateTokenInfo> = vec![];
-
-    for log in logs {
-        // check program invocation
-        if log.contains(&format!("Program {} invoke", PUMPFUN_PROGRAM_ID)) {
-            invoke_depth += 1;
-            if invoke_depth == 1 {
-                // Only reset state at top level call
-                current_instruction = None;
-                program_data.clear();
-                last_data_len = 0;
+    // updates
+    loop {
+        let msg = tokio::select! {
+            msg = rx.recv() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            _ = pipeline.tick() => continue,
+            _ = reload_signal.recv() => {
+                if let Err(e) = pipeline.filter.reload_creator_lists() {
+                    error!("Failed to reload creator lists: {:?}", e);
+                }
+                continue;
             }
-            continue;
+            _ = tokio::signal::ctrl_c() => {
+                source_ended = false;
+                break;
+            }
+        };
+        health.record_message();
+        if let Some(recorder) = &recorder {
+            recorder.record(&msg);
+        }
+        let slot = update_slot(&msg);
+        if let Some(event) = gaps.observe(health.reconnects(), slot) {
+            pipeline.send_control(event);
         }
 
-        // skip if not
-        if invoke_depth == 0 {
+        if let Err(e) = pipeline.process_update(msg).await {
+            error!("Error processing account update: {:?}", e);
             continue;
         }
+        if let Some(slot) = slot {
+            pipeline.record_processed(slot);
+        }
+        health.set_launches(pipeline.stats.session.launches);
 
-        // identify instruction type (only at top level)
-        if invoke_depth == 1 && log.contains("Program log: Instruction:") {
-            if log.contains("Create") {
-                current_instruction = Some("create");
-            } else if log.contains("Buy") || log.contains("Sell") {
-                current_instruction = Some("trade");
-            }
-            continue;
+        // `--until` doubles as a soft stop for live streams, a recording plays to its end
+        if !source_finite && pipeline.filter.is_expired() {
+            println!("Reached --until, stopping stream");
+            source_ended = false;
+            break;
         }
+    }
 
-        // collect program data
-        if log.starts_with("Program data: ") {
-            let data = log.trim_start_matches("Program data: ");
-            if data.len() > last_data_len {
-                program_data = data.to_string();
-                last_data_len = data.len();
-            }
+    pipeline.stats.print_summary();
+    pipeline.send_control(ControlEvent::StreamStopped {
+        at: Utc::now(),
+        launches: pipeline.stats.session.launches,
+    });
+    pipeline.close().await;
+    if let Some(recorder) = recorder {
+        recorder.close().await;
+    }
+
+    // a source that died exits non-zero, for a supervisor to restart
+    if source_ended {
+        source.await??;
+        if !source_finite {
+            anyhow::bail!("{} source ended without an error", source_name);
         }
+    }
+    Ok(())
+}
 
-        // check if program ends
-        if log.contains(&format!("Program {} success", PUMPFUN_PROGRAM_ID)) {
-            invoke_depth -= 1;
-            if invoke_depth == 0 {
-                // Only process data when top level program ends
-                if let Some(instruction_type) = current_instruction {
-                    if !program_data.is_empty() {
-                        match instruction_type {
-                            "create" => {
-                                if let Ok(token_info) = parse_create_token_data(&program_data) {
-                                    instructions.push(token_info);
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
+fn update_slot(update: &SubscribeUpdate) -> Option<u64> {
+    match update.update_oneof.as_ref()? {
+        UpdateOneof::Transaction(transaction) => Some(transaction.slot),
+        UpdateOneof::Slot(slot) => Some(slot.slot),
+        _ => None,
+    }
+}
+
+/// SIGHUP, which reloads the creator lists. Only caught when there's a list
+/// to reload, otherwise a hangup still terminates; never fires off unix.
+struct ReloadSignal {
+    #[cfg(unix)]
+    hangup: Option<tokio::signal::unix::Signal>,
+}
+
+impl ReloadSignal {
+    fn new(enabled: bool) -> Self {
+        #[cfg(not(unix))]
+        let _ = enabled;
+        Self {
+            #[cfg(unix)]
+            hangup: enabled
+                .then(|| {
+                    tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok()
+                })
+                .flatten(),
         }
     }
 
-    Ok(instructions)
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(hangup) = &mut self.hangup {
+            hangup.recv().await;
+            return;
+        }
+        std::future::pending().await
+    }
 }
@@ -0,0 +1,45 @@
+//! Off-chain metadata behind a launch's `uri`, fetched with bounded
+//! concurrency so a burst of launches can't flood the gateway.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Semaphore;
+
+use crate::parser::TokenMetadata;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone)]
+pub struct MetadataFetcher {
+    client: reqwest::Client,
+    permits: Arc<Semaphore>,
+}
+
+impl MetadataFetcher {
+    pub fn new(concurrency: usize) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            permits: Arc::new(Semaphore::new(concurrency.max(1))),
+        })
+    }
+
+    /// Waits for a permit, then GETs and decodes `uri`.
+    pub async fn fetch(&self, uri: &str) -> anyhow::Result<TokenMetadata> {
+        let _permit = self.permits.acquire().await?;
+
+        self.client
+            .get(uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TokenMetadata>()
+            .await
+            .map_err(|e| anyhow::anyhow!("Invalid metadata at {}: {}", uri, e))
+    }
+}
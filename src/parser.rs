@@ -0,0 +1,960 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as base64};
+use borsh::{BorshDeserialize, BorshSerialize};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use solana_pubkey::{Pubkey, pubkey};
+
+use crate::error::PumpfunError;
+
+pub const PUMPFUN_PROGRAM_ID: Pubkey = pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+
+// pump-swap, the AMM bonding curves migrate to once complete
+pub const PUMPSWAP_PROGRAM_ID: Pubkey = pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+
+// first 8 bytes of sha256("event:CreateEvent")
+pub const CREATE_EVENT_DISCRIMINATOR: [u8; 8] = [27, 114, 169, 77, 222, 235, 99, 118];
+
+// first 8 bytes of sha256("event:TradeEvent")
+pub const TRADE_EVENT_DISCRIMINATOR: [u8; 8] = [189, 219, 127, 211, 78, 230, 97, 238];
+
+// pump-swap's, first 8 bytes of sha256("event:CreatePoolEvent") / "event:BuyEvent" / "event:SellEvent"
+pub const AMM_CREATE_POOL_EVENT_DISCRIMINATOR: [u8; 8] = [177, 49, 12, 210, 160, 118, 167, 116];
+pub const AMM_BUY_EVENT_DISCRIMINATOR: [u8; 8] = [103, 244, 82, 31, 44, 245, 119, 119];
+pub const AMM_SELL_EVENT_DISCRIMINATOR: [u8; 8] = [62, 47, 55, 10, 165, 3, 220, 42];
+
+// Pumpfun mints use 6 decimals, the create event doesn't carry them
+pub const PUMPFUN_TOKEN_DECIMALS: u8 = 6;
+
+// CreateEvent fields after the user: creator, timestamp, virtual token and
+// sol reserves, real token reserves and the token total supply
+const CREATE_EVENT_TAIL_LEN: usize = 32 + 8 + 4 * 8;
+
+/// Pumpfun instructions that are neither a create nor a trade. Their data,
+/// including anything logged by their CPIs, is never read as an event.
+pub const PUMPFUN_ADMIN_INSTRUCTIONS: &[&str] = &[
+    "Initialize",
+    "SetParams",
+    "Withdraw",
+    "ExtendAccount",
+    "Migrate",
+    "UpdateGlobalAuthority",
+    "SetCreator",
+    "SetMetaplexCreator",
+    "CollectCreatorFee",
+];
+pub const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+// every bonding curve starts from the same virtual reserves over a fixed supply (base units)
+pub const PUMPFUN_INITIAL_VIRTUAL_SOL_RESERVES: u64 = 30_000_000_000;
+pub const PUMPFUN_INITIAL_VIRTUAL_TOKEN_RESERVES: u64 = 1_073_000_000_000_000;
+pub const PUMPFUN_TOKEN_SUPPLY: u64 = 1_000_000_000_000_000;
+
+// format of `CreateTokenInfo::created_at`, always UTC
+pub const CREATED_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[serde_as]
+#[derive(
+    Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, Serialize, Deserialize,
+)]
+pub struct CreateTokenInfo {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub mint: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub bonding_curve: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub user: Pubkey,
+    pub created_at: String,
+    // ms between the provider timestamp, or the block time without one, and
+    // local detection
+    #[serde(default)]
+    pub detection_latency_ms: Option<i64>,
+    // Pumpfun invocations and deepest nesting of them in the transaction
+    #[serde(default)]
+    pub invoke_count: u32,
+    #[serde(default)]
+    pub max_invoke_depth: u32,
+    // set when the event data was cut short and only some fields were decoded
+    #[serde(default)]
+    pub partial: bool,
+    // off-chain JSON behind `uri`, when `--fetch-metadata` got it
+    #[serde(default)]
+    pub metadata: Option<TokenMetadata>,
+    // earlier launch in the session this one looks copied from
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub similar_to: Option<Pubkey>,
+    // `--verify-mint-onchain` result, None when not checked or the RPC failed
+    #[serde(default)]
+    pub mint_verified: Option<bool>,
+    // slot and in-block index of the create transaction, the JSON log's sort order
+    #[serde(default)]
+    pub slot: u64,
+    #[serde(default)]
+    pub tx_index: u64,
+    // commitment the launch was streamed at, None for the provider's default
+    #[serde(default)]
+    pub commitment: Option<Commitment>,
+    // `--confirm-finality` result, None when not checked or the RPC failed
+    #[serde(default)]
+    pub finalized: Option<bool>,
+    // market cap at the curve's starting price, with `--sol-price-source`
+    #[serde(default)]
+    pub initial_market_cap_usd: Option<f64>,
+    // total supply (base units) from the create event, None when it predates
+    // it; see `supply()` for the fallback
+    #[serde(default)]
+    pub token_supply: Option<u64>,
+    // never set, the event has no decimals; kept so existing records still
+    // read, see `decimals()`
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    // written under `--max-per-second`, so a sample of the launches rather than all
+    #[serde(default)]
+    pub sampled: bool,
+    // launches this session sharing this one's metadata, itself included
+    #[serde(default)]
+    pub metadata_reuse_count: Option<u32>,
+    // `metadata_reuse_count` reached `--flag-metadata-reuse`
+    #[serde(default)]
+    pub suspected_farm: bool,
+    // the creator's net SOL outflow in the create transaction: fees, rent and
+    // any dev buy bundled with it. None when the creator isn't in the account keys
+    #[serde(default)]
+    pub creator_initial_sol_spent: Option<f64>,
+    // the creator's own buys of the mint in the create transaction, SOL in and
+    // base-unit tokens out; None when it didn't buy
+    #[serde(default)]
+    pub dev_buy_sol: Option<f64>,
+    #[serde(default)]
+    pub dev_buy_tokens: Option<u64>,
+    // the first distinct buyers after the create, with `--first-buyers`
+    #[serde(default)]
+    pub first_buyers: Option<Vec<FirstBuyer>>,
+    // `--check-fresh-wallet` result for the creator, None when not checked or the RPC failed
+    #[serde(default)]
+    pub fresh_wallet: Option<bool>,
+    // fields computed by a `--transform` hook, by name
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
+    // unix seconds, the create event's on-chain timestamp; None when it predates it
+    #[serde(default)]
+    pub block_time: Option<i64>,
+}
+
+/// Solana commitment level a transaction was observed at.
+#[cfg_attr(feature = "stream", derive(clap::ValueEnum))]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    /// seen by the node, may still be dropped on a fork
+    Processed,
+    /// voted on by a supermajority
+    Confirmed,
+    /// rooted, can no longer be rolled back
+    Finalized,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct TokenMetadata {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub twitter: Option<String>,
+    #[serde(default)]
+    pub telegram: Option<String>,
+    #[serde(default)]
+    pub website: Option<String>,
+}
+
+impl CreateTokenInfo {
+    /// Always `PUMPFUN_TOKEN_DECIMALS`, whatever an older record's `decimals` says.
+    pub fn decimals(&self) -> u8 {
+        PUMPFUN_TOKEN_DECIMALS
+    }
+
+    /// Total supply in base units.
+    pub fn supply(&self) -> u64 {
+        self.token_supply.unwrap_or(PUMPFUN_TOKEN_SUPPLY)
+    }
+
+    /// SOL per whole token at the curve's starting price.
+    pub fn initial_price_sol(&self) -> f64 {
+        (PUMPFUN_INITIAL_VIRTUAL_SOL_RESERVES as f64 / LAMPORTS_PER_SOL)
+            / (PUMPFUN_INITIAL_VIRTUAL_TOKEN_RESERVES as f64 / self.unit())
+    }
+
+    /// Market cap in SOL at the curve's starting price.
+    pub fn initial_market_cap_sol(&self) -> f64 {
+        self.initial_price_sol() * (self.supply() as f64 / self.unit())
+    }
+
+    // base units per whole token
+    fn unit(&self) -> f64 {
+        10f64.powi(self.decimals() as i32)
+    }
+
+    pub fn created_at_utc(&self) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(&self.created_at, CREATED_AT_FORMAT)
+            .ok()
+            .map(|time| time.and_utc())
+    }
+
+    /// When the launch happened on-chain, or for an event without a
+    /// timestamp, when it was detected.
+    pub fn launched_at(&self) -> Option<DateTime<Utc>> {
+        match self.block_time {
+            Some(block_time) => DateTime::from_timestamp(block_time, 0),
+            None => self.created_at_utc(),
+        }
+    }
+}
+
+/// One of a launch's first buyers: SOL in and base-unit tokens out over
+/// their buys in the launch's window.
+#[serde_as]
+#[derive(
+    Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, Serialize, Deserialize,
+)]
+pub struct FirstBuyer {
+    #[serde_as(as = "DisplayFromStr")]
+    pub user: Pubkey,
+    pub sol: f64,
+    pub tokens: u64,
+}
+
+#[serde_as]
+#[derive(
+    Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, Serialize, Deserialize,
+)]
+pub struct TradeInfo {
+    #[serde_as(as = "DisplayFromStr")]
+    pub mint: Pubkey,
+    // lamports
+    pub sol_amount: u64,
+    // token base units
+    pub token_amount: u64,
+    pub is_buy: bool,
+    #[serde_as(as = "DisplayFromStr")]
+    pub user: Pubkey,
+    pub timestamp: i64,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+}
+
+impl TradeInfo {
+    pub fn sol(&self) -> f64 {
+        self.sol_amount as f64 / LAMPORTS_PER_SOL
+    }
+
+    pub fn tokens(&self) -> f64 {
+        self.token_amount as f64 / 10f64.powi(PUMPFUN_TOKEN_DECIMALS as i32)
+    }
+
+    /// SOL per whole token on the curve after this trade.
+    pub fn price_sol(&self) -> f64 {
+        if self.virtual_token_reserves == 0 {
+            return 0.0;
+        }
+        (self.virtual_sol_reserves as f64 / LAMPORTS_PER_SOL)
+            / (self.virtual_token_reserves as f64 / 10f64.powi(PUMPFUN_TOKEN_DECIMALS as i32))
+    }
+}
+
+/// A pump-swap pool, created when a bonding curve migrates.
+#[serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AmmPoolInfo {
+    #[serde_as(as = "DisplayFromStr")]
+    pub pool: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub creator: Pubkey,
+    // the launch's mint, for migrated curves
+    #[serde_as(as = "DisplayFromStr")]
+    pub base_mint: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub quote_mint: Pubkey,
+    // base units of each side deposited
+    pub base_amount_in: u64,
+    pub quote_amount_in: u64,
+    pub timestamp: i64,
+}
+
+/// A buy or sell against a pump-swap pool.
+#[serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AmmSwapInfo {
+    #[serde_as(as = "DisplayFromStr")]
+    pub pool: Pubkey,
+    #[serde_as(as = "DisplayFromStr")]
+    pub user: Pubkey,
+    // buying base (the token) with quote (usually wrapped SOL)
+    pub is_buy: bool,
+    // base units, out for a buy and in for a sell; likewise quote
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub pool_base_token_reserves: u64,
+    pub pool_quote_token_reserves: u64,
+    pub timestamp: i64,
+}
+
+impl AmmPoolInfo {
+    /// The quote side in SOL, for the usual wrapped-SOL quote mint.
+    pub fn quote_sol(&self) -> f64 {
+        self.quote_amount_in as f64 / LAMPORTS_PER_SOL
+    }
+}
+
+impl AmmSwapInfo {
+    /// The quote side in SOL, for the usual wrapped-SOL quote mint.
+    pub fn quote_sol(&self) -> f64 {
+        self.quote_amount as f64 / LAMPORTS_PER_SOL
+    }
+}
+
+// events are consumed right after parsing, boxing the create buys nothing
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PumpfunEvent {
+    Create(CreateTokenInfo),
+    Trade(TradeInfo),
+    AmmCreatePool(AmmPoolInfo),
+    AmmSwap(AmmSwapInfo),
+}
+
+/// An outermost Pumpfun invocation (top-level for creates) that produced an event.
+#[derive(Clone, Debug)]
+pub struct Invocation {
+    pub event: PumpfunEvent,
+    // index range of the invocation's lines in the transaction logs
+    pub logs: Range<usize>,
+    // base64 `Program data:` the event was decoded from
+    pub program_data: String,
+}
+
+// longest string field accepted by default, far above Pumpfun's own limits
+pub const DEFAULT_MAX_FIELD_LEN: usize = 4096;
+
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    // keep records whose data is cut short once name and symbol are decoded
+    pub allow_partial: bool,
+    // longest declared string length read, checked before anything is copied
+    pub max_field_len: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            allow_partial: false,
+            max_field_len: DEFAULT_MAX_FIELD_LEN,
+        }
+    }
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&data[..4]);
+    u32::from_le_bytes(bytes)
+}
+
+// sequential reader over decoded event bytes
+struct DataReader<'a> {
+    data: &'a [u8],
+    cursor: usize,
+    max_field_len: usize,
+}
+
+impl<'a> DataReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            cursor: 0,
+            max_field_len: DEFAULT_MAX_FIELD_LEN,
+        }
+    }
+
+    fn ensure(&self, field: &str, needed: usize) -> Result<(), PumpfunError> {
+        if self.cursor + needed > self.data.len() {
+            return Err(PumpfunError::Truncated {
+                field: field.to_string(),
+                needed,
+            });
+        }
+        Ok(())
+    }
+
+    fn read_discriminator(&mut self, expected: [u8; 8]) -> Result<(), PumpfunError> {
+        self.ensure("discriminator", 8)?;
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&self.data[self.cursor..self.cursor + 8]);
+        self.cursor += 8;
+
+        if discriminator != expected {
+            return Err(PumpfunError::InvalidDiscriminator(discriminator));
+        }
+        Ok(())
+    }
+
+    fn read_string(&mut self, field: &str) -> Result<String, PumpfunError> {
+        self.ensure(&format!("{} length", field), 4)?;
+        let len = read_u32(&self.data[self.cursor..]) as usize;
+        self.cursor += 4;
+
+        if len > self.max_field_len {
+            return Err(PumpfunError::FieldTooLong {
+                field: field.to_string(),
+                len,
+                max: self.max_field_len,
+            });
+        }
+        self.ensure(field, len)?;
+        let value = String::from_utf8(self.data[self.cursor..self.cursor + len].to_vec())
+            .map_err(|e| PumpfunError::Decode(format!("Invalid UTF-8 in {}: {}", field, e)))?;
+        self.cursor += len;
+
+        Ok(value)
+    }
+
+    fn read_u64(&mut self, field: &str) -> Result<u64, PumpfunError> {
+        self.ensure(field, 8)?;
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.data[self.cursor..self.cursor + 8]);
+        self.cursor += 8;
+
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.cursor)
+    }
+
+    fn read_u8(&mut self, field: &str) -> Result<u8, PumpfunError> {
+        self.ensure(field, 1)?;
+        let value = self.data[self.cursor];
+        self.cursor += 1;
+
+        Ok(value)
+    }
+
+    fn skip(&mut self, field: &str, len: usize) -> Result<(), PumpfunError> {
+        self.ensure(field, len)?;
+        self.cursor += len;
+        Ok(())
+    }
+
+    fn read_i64(&mut self, field: &str) -> Result<i64, PumpfunError> {
+        self.read_u64(field).map(|value| value as i64)
+    }
+
+    fn read_bool(&mut self, field: &str) -> Result<bool, PumpfunError> {
+        self.read_u8(field).map(|value| value != 0)
+    }
+
+    fn read_pubkey(&mut self, field: &str) -> Result<Pubkey, PumpfunError> {
+        self.ensure(field, 32)?;
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&self.data[self.cursor..self.cursor + 32]);
+        self.cursor += 32;
+
+        Ok(Pubkey::new_from_array(bytes))
+    }
+}
+
+fn decode_program_data(data: &str, allow_partial: bool) -> Result<Vec<u8>, PumpfunError> {
+    let decode_error = |e: base64::DecodeError| PumpfunError::Decode(format!("base64: {}", e));
+    match base64.decode(data) {
+        Ok(decoded) => Ok(decoded),
+        // a truncated line usually ends mid base64 group, decode the whole groups
+        Err(_) if allow_partial => base64
+            .decode(&data.as_bytes()[..data.len() / 4 * 4])
+            .map_err(decode_error),
+        Err(e) => Err(decode_error(e)),
+    }
+}
+
+pub fn parse_create_token_data(data: &str) -> Result<CreateTokenInfo, PumpfunError> {
+    parse_create_token_data_with(data, &ParseOptions::default())
+}
+
+pub fn parse_create_token_data_with(
+    data: &str,
+    options: &ParseOptions,
+) -> Result<CreateTokenInfo, PumpfunError> {
+    let decoded = decode_program_data(data, options.allow_partial)?;
+    let mut reader = DataReader::new(&decoded);
+    reader.max_field_len = options.max_field_len;
+    reader.read_discriminator(CREATE_EVENT_DISCRIMINATOR)?;
+
+    // name and symbol are required even for partial records
+    let mut token_info = CreateTokenInfo {
+        name: reader.read_string("name")?,
+        symbol: reader.read_string("symbol")?,
+        created_at: Utc::now().format(CREATED_AT_FORMAT).to_string(),
+        ..Default::default()
+    };
+
+    match read_create_tail(&mut reader, &mut token_info) {
+        Ok(()) => {}
+        Err(PumpfunError::Truncated { .. }) if options.allow_partial => token_info.partial = true,
+        Err(e) => return Err(e),
+    }
+
+    Ok(token_info)
+}
+
+// uri and pubkeys, filled in place so a partial record keeps what was read
+fn read_create_tail(
+    reader: &mut DataReader,
+    token_info: &mut CreateTokenInfo,
+) -> Result<(), PumpfunError> {
+    token_info.uri = reader.read_string("uri")?;
+    token_info.mint = reader.read_pubkey("mint")?;
+    token_info.bonding_curve = reader.read_pubkey("bonding curve")?;
+    token_info.user = reader.read_pubkey("user")?;
+
+    // older events end at the user; current ones go on with the creator,
+    // timestamp, three reserves and the total supply, read only when all there
+    if reader.remaining() >= CREATE_EVENT_TAIL_LEN {
+        reader.read_pubkey("creator")?;
+        let timestamp = reader.read_i64("timestamp")?;
+        reader.read_u64("virtual token reserves")?;
+        reader.read_u64("virtual sol reserves")?;
+        reader.read_u64("real token reserves")?;
+        token_info.token_supply = Some(reader.read_u64("token total supply")?);
+        token_info.block_time = Some(timestamp);
+    }
+
+    Ok(())
+}
+
+pub fn parse_trade_data(data: &str) -> Result<TradeInfo, PumpfunError> {
+    let decoded = decode_program_data(data, false)?;
+    let mut reader = DataReader::new(&decoded);
+    reader.read_discriminator(TRADE_EVENT_DISCRIMINATOR)?;
+
+    Ok(TradeInfo {
+        mint: reader.read_pubkey("mint")?,
+        sol_amount: reader.read_u64("sol amount")?,
+        token_amount: reader.read_u64("token amount")?,
+        is_buy: reader.read_bool("is buy")?,
+        user: reader.read_pubkey("user")?,
+        timestamp: reader.read_i64("timestamp")?,
+        virtual_sol_reserves: reader.read_u64("virtual sol reserves")?,
+        virtual_token_reserves: reader.read_u64("virtual token reserves")?,
+    })
+}
+
+/// A pump-swap `Program data:` event; None for ones that aren't a pool or a swap.
+pub fn parse_amm_data(data: &str) -> Result<Option<PumpfunEvent>, PumpfunError> {
+    let decoded = decode_program_data(data, false)?;
+    let mut reader = DataReader::new(&decoded);
+    reader.ensure("discriminator", 8)?;
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&decoded[..8]);
+
+    match discriminator {
+        AMM_CREATE_POOL_EVENT_DISCRIMINATOR => {
+            reader.read_discriminator(AMM_CREATE_POOL_EVENT_DISCRIMINATOR)?;
+            let timestamp = reader.read_i64("timestamp")?;
+            reader.skip("index", 2)?;
+            let creator = reader.read_pubkey("creator")?;
+            let base_mint = reader.read_pubkey("base mint")?;
+            let quote_mint = reader.read_pubkey("quote mint")?;
+            reader.skip("mint decimals", 2)?;
+            let base_amount_in = reader.read_u64("base amount in")?;
+            let quote_amount_in = reader.read_u64("quote amount in")?;
+            // pool amounts, minimum and initial liquidity, lp tokens out, then the bump
+            reader.skip("pool amounts", 5 * 8 + 1)?;
+            Ok(Some(PumpfunEvent::AmmCreatePool(AmmPoolInfo {
+                pool: reader.read_pubkey("pool")?,
+                creator,
+                base_mint,
+                quote_mint,
+                base_amount_in,
+                quote_amount_in,
+                timestamp,
+            })))
+        }
+        AMM_BUY_EVENT_DISCRIMINATOR | AMM_SELL_EVENT_DISCRIMINATOR => {
+            let is_buy = discriminator == AMM_BUY_EVENT_DISCRIMINATOR;
+            reader.read_discriminator(discriminator)?;
+            // buys and sells share a layout up to the pool and user
+            let timestamp = reader.read_i64("timestamp")?;
+            let base_amount = reader.read_u64("base amount")?;
+            reader.skip("quote limit and user reserves", 3 * 8)?;
+            let pool_base_token_reserves = reader.read_u64("pool base token reserves")?;
+            let pool_quote_token_reserves = reader.read_u64("pool quote token reserves")?;
+            let quote_amount = reader.read_u64("quote amount")?;
+            reader.skip("fees", 6 * 8)?;
+            Ok(Some(PumpfunEvent::AmmSwap(AmmSwapInfo {
+                pool: reader.read_pubkey("pool")?,
+                user: reader.read_pubkey("user")?,
+                is_buy,
+                base_amount,
+                quote_amount,
+                pool_base_token_reserves,
+                pool_quote_token_reserves,
+                timestamp,
+            })))
+        }
+        _ => Ok(None),
+    }
+}
+
+pub fn parse_instruction(logs: &[String]) -> Result<Vec<CreateTokenInfo>, PumpfunError> {
+    parse_instruction_with(logs, &ParseOptions::default())
+}
+
+/// Creates only, see `parse_events_with` for trades too.
+pub fn parse_instruction_with(
+    logs: &[String],
+    options: &ParseOptions,
+) -> Result<Vec<CreateTokenInfo>, PumpfunError> {
+    Ok(parse_events_with(logs, options)?
+        .into_iter()
+        .filter_map(|event| match event {
+            PumpfunEvent::Create(token_info) => Some(token_info),
+            _ => None,
+        })
+        .collect())
+}
+
+pub fn parse_events(logs: &[String]) -> Result<Vec<PumpfunEvent>, PumpfunError> {
+    parse_events_with(logs, &ParseOptions::default())
+}
+
+pub fn parse_events_with(
+    logs: &[String],
+    options: &ParseOptions,
+) -> Result<Vec<PumpfunEvent>, PumpfunError> {
+    Ok(parse_invocations_with(logs, options)?
+        .into_iter()
+        .map(|invocation| invocation.event)
+        .collect())
+}
+
+/// Events together with the log lines and `Program data:` they were parsed
+/// from, for debugging the parser against live data.
+pub fn parse_invocations_with(
+    logs: &[String],
+    options: &ParseOptions,
+) -> Result<Vec<Invocation>, PumpfunError> {
+    let mut current_instruction = None;
+    let mut program_data = String::new();
+    let mut max_invoke_depth = 0;
+    let mut invoke_count = 0;
+    let mut last_data_len = 0;
+    // whether the current Pumpfun invocation is a top-level instruction of
+    // the transaction rather than a CPI from another program (routers,
+    // aggregators). Only creates require it, most trades come in by CPI
+    let mut is_top_level = false;
+    let mut invocation_start = 0;
+    // program ids of the open invocations, outermost first. Each invoke's
+    // bracketed depth truncates it, so a dropped `success` line can't leave
+    // a frame open for the rest of the transaction
+    let mut frames: Vec<&str> = vec![];
+    // Anchor event-CPI: `Program data:` logged by a direct self-invoke, the
+    // open one's lines and the first that logged exactly one, which takes
+    // precedence over the longest line
+    let mut self_cpi_data: Option<Vec<String>> = None;
+    let mut event_cpi_data: Option<String> = None;
+
+    let mut invocations: Vec<Invocation> = vec![];
+
+    let program_id = PUMPFUN_PROGRAM_ID.to_string();
+    let is_self_cpi = |frames: &[&str]| matches!(frames, [.., outer, inner] if *outer == program_id && *inner == program_id);
+
+    for (index, log) in logs.iter().enumerate() {
+        // check program invocation
+        if let Some((program, depth)) = parse_invoke_line(log) {
+            frames.truncate(depth.saturating_sub(1));
+            frames.push(program);
+            if program == program_id {
+                invoke_count += 1;
+                let invoke_depth = pumpfun_depth(&frames, &program_id);
+                max_invoke_depth = max_invoke_depth.max(invoke_depth);
+                if invoke_depth == 1 {
+                    // Only reset state at top level call
+                    current_instruction = None;
+                    program_data.clear();
+                    last_data_len = 0;
+                    event_cpi_data = None;
+                    is_top_level = depth == 1;
+                    invocation_start = index;
+                }
+                if is_self_cpi(&frames) {
+                    self_cpi_data = Some(vec![]);
+                }
+            }
+            continue;
+        }
+
+        // check if a program ends, closing any frames inside it whose own line is missing
+        if let Some((program, succeeded)) = parse_exit_line(log) {
+            let Some(position) = frames.iter().rposition(|frame| *frame == program) else {
+                continue;
+            };
+            let ends_pumpfun =
+                program == program_id && pumpfun_depth(&frames[..position], &program_id) == 0;
+            if is_self_cpi(&frames[..position + 1])
+                && let Some(data) = self_cpi_data.take()
+                && let [data] = data.as_slice()
+                && event_cpi_data.is_none()
+            {
+                event_cpi_data = Some(data.clone());
+            }
+            frames.truncate(position);
+
+            // Only process data when top level program ends
+            let program_data = event_cpi_data.as_ref().unwrap_or(&program_data);
+            if ends_pumpfun && succeeded && !program_data.is_empty() {
+                let event = match current_instruction {
+                    Some("create") if is_top_level => {
+                        parse_create_token_data_with(program_data, options)
+                            .ok()
+                            .map(PumpfunEvent::Create)
+                    }
+                    Some("trade") => parse_trade_data(program_data).ok().map(PumpfunEvent::Trade),
+                    _ => None,
+                };
+                if let Some(event) = event {
+                    invocations.push(Invocation {
+                        event,
+                        logs: invocation_start..index + 1,
+                        program_data: program_data.clone(),
+                    });
+                }
+            }
+            continue;
+        }
+
+        // skip if not
+        let invoke_depth = pumpfun_depth(&frames, &program_id);
+        if invoke_depth == 0 {
+            continue;
+        }
+
+        // identify instruction type (only for the outermost Pumpfun invocation,
+        // never a Pumpfun self-CPI), from its own first line; later ones come from its CPIs
+        if invoke_depth == 1
+            && current_instruction.is_none()
+            && let Some(name) = log.strip_prefix("Program log: Instruction: ")
+        {
+            current_instruction = Some(match name.trim() {
+                "Create" => "create",
+                "Buy" | "Sell" => "trade",
+                name if PUMPFUN_ADMIN_INSTRUCTIONS.contains(&name) => "admin",
+                _ => "unknown",
+            });
+            continue;
+        }
+
+        // collect program data, an admin instruction's is never an event
+        if current_instruction != Some("admin")
+            && let Some(data) = log.strip_prefix("Program data: ")
+        {
+            if is_self_cpi(&frames)
+                && let Some(self_cpi_data) = &mut self_cpi_data
+            {
+                self_cpi_data.push(data.to_string());
+            }
+            if data.len() > last_data_len {
+                program_data = data.to_string();
+                last_data_len = data.len();
+            }
+        }
+    }
+
+    let buys: Vec<TradeInfo> = invocations
+        .iter()
+        .filter_map(|invocation| match &invocation.event {
+            PumpfunEvent::Trade(trade_info) if trade_info.is_buy => Some(trade_info.clone()),
+            _ => None,
+        })
+        .collect();
+    for invocation in &mut invocations {
+        if let PumpfunEvent::Create(token_info) = &mut invocation.event {
+            token_info.invoke_count = invoke_count;
+            token_info.max_invoke_depth = max_invoke_depth;
+
+            let dev_buys = buys.iter().filter(|trade_info| {
+                trade_info.mint == token_info.mint && trade_info.user == token_info.user
+            });
+            let (sol_amount, token_amount) = dev_buys.fold((0, 0), |(sol, tokens), trade_info| {
+                (
+                    sol + trade_info.sol_amount,
+                    tokens + trade_info.token_amount,
+                )
+            });
+            if token_amount > 0 {
+                token_info.dev_buy_sol = Some(sol_amount as f64 / LAMPORTS_PER_SOL);
+                token_info.dev_buy_tokens = Some(token_amount);
+            }
+        }
+    }
+
+    Ok(invocations)
+}
+
+/// The name of every Pumpfun instruction in the logs, in order, from each
+/// invocation's `Program log: Instruction:` line; CPIs into Pumpfun included.
+/// Names the parser has no event for show up here too.
+pub fn parse_instruction_names(logs: &[String]) -> Vec<&str> {
+    let program_id = PUMPFUN_PROGRAM_ID.to_string();
+    let mut frames: Vec<&str> = vec![];
+    // the innermost Pumpfun frame hasn't logged its name yet
+    let mut unnamed = false;
+    let mut names = vec![];
+
+    for log in logs {
+        if let Some((program, depth)) = parse_invoke_line(log) {
+            frames.truncate(depth.saturating_sub(1));
+            frames.push(program);
+            unnamed = program == program_id;
+            continue;
+        }
+        if let Some((program, _)) = parse_exit_line(log) {
+            if let Some(position) = frames.iter().rposition(|frame| *frame == program) {
+                frames.truncate(position);
+            }
+            unnamed = false;
+            continue;
+        }
+        if unnamed
+            && frames.last() == Some(&program_id.as_str())
+            && let Some(name) = log.strip_prefix("Program log: Instruction: ")
+        {
+            names.push(name.trim());
+            unnamed = false;
+        }
+    }
+
+    names
+}
+
+/// Events of every program in `programs` (Pumpfun and pump-swap) that
+/// appears in the logs, in log order.
+pub fn parse_program_invocations_with(
+    logs: &[String],
+    programs: &[Pubkey],
+    options: &ParseOptions,
+) -> Result<Vec<Invocation>, PumpfunError> {
+    let invoked = |program_id: &Pubkey| {
+        let id = program_id.to_string();
+        programs.contains(program_id)
+            && logs
+                .iter()
+                .any(|log| parse_invoke_line(log).is_some_and(|(program, _)| program == id))
+    };
+
+    let mut invocations = vec![];
+    if invoked(&PUMPFUN_PROGRAM_ID) {
+        invocations.extend(parse_invocations_with(logs, options)?);
+    }
+    if invoked(&PUMPSWAP_PROGRAM_ID) {
+        invocations.extend(parse_amm_invocations(logs)?);
+    }
+    invocations.sort_by_key(|invocation| invocation.logs.start);
+
+    Ok(invocations)
+}
+
+/// pump-swap events, each from the AMM frame that logged it. Unlike Pumpfun
+/// events these are kept when the AMM is reached through a CPI, a migration
+/// creates its pool from inside Pumpfun's `Migrate`.
+pub fn parse_amm_invocations(logs: &[String]) -> Result<Vec<Invocation>, PumpfunError> {
+    let program_id = PUMPSWAP_PROGRAM_ID.to_string();
+    let mut frames: Vec<&str> = vec![];
+    // events of the open AMM frame, with where the frame started
+    let mut pending: Vec<(PumpfunEvent, String)> = vec![];
+    let mut frame_start = 0;
+    let mut invocations = vec![];
+
+    for (index, log) in logs.iter().enumerate() {
+        if let Some((program, depth)) = parse_invoke_line(log) {
+            frames.truncate(depth.saturating_sub(1));
+            frames.push(program);
+            if program == program_id {
+                pending.clear();
+                frame_start = index;
+            }
+            continue;
+        }
+
+        if let Some((program, succeeded)) = parse_exit_line(log) {
+            let Some(position) = frames.iter().rposition(|frame| *frame == program) else {
+                continue;
+            };
+            frames.truncate(position);
+            if program == program_id {
+                for (event, program_data) in pending.drain(..) {
+                    if succeeded {
+                        invocations.push(Invocation {
+                            event,
+                            logs: frame_start..index + 1,
+                            program_data,
+                        });
+                    }
+                }
+            }
+            continue;
+        }
+
+        if frames.last() == Some(&program_id.as_str())
+            && let Some(data) = log.strip_prefix("Program data: ")
+            && let Ok(Some(event)) = parse_amm_data(data)
+        {
+            pending.push((event, data.to_string()));
+        }
+    }
+
+    Ok(invocations)
+}
+
+// `Program <id> invoke [<depth>]`
+fn parse_invoke_line(log: &str) -> Option<(&str, usize)> {
+    let (program, depth) = log.strip_prefix("Program ")?.split_once(" invoke [")?;
+    // `Program log: Program X invoke [1]` is a program's own message
+    if program.contains(' ') {
+        return None;
+    }
+    Some((program, depth.strip_suffix(']')?.parse().ok()?))
+}
+
+// `Program <id> success` or `Program <id> failed: <reason>`
+fn parse_exit_line(log: &str) -> Option<(&str, bool)> {
+    let rest = log.strip_prefix("Program ")?;
+    if let Some(program) = rest.strip_suffix(" success") {
+        return (!program.contains(' ')).then_some((program, true));
+    }
+    let (program, _) = rest.split_once(" failed")?;
+    (!program.contains(' ')).then_some((program, false))
+}
+
+// Pumpfun invocations among the open frames, i.e. how deeply Pumpfun is nested in itself
+fn pumpfun_depth(frames: &[&str], program_id: &str) -> u32 {
+    frames.iter().filter(|frame| **frame == program_id).count() as u32
+}
@@ -0,0 +1,1226 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use tokio::sync::mpsc;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::{Instrument, Span, info_span};
+use yellowstone_grpc_proto::prelude::{SubscribeUpdate, subscribe_update::UpdateOneof};
+
+use solana_pumpfun::finality::FinalityChecker;
+use solana_pumpfun::metadata::MetadataFetcher;
+use solana_pumpfun::parser::{
+    AmmPoolInfo, AmmSwapInfo, Commitment, CreateTokenInfo, LAMPORTS_PER_SOL, ParseOptions,
+    PumpfunEvent, TradeInfo, parse_events_with, parse_instruction_names,
+    parse_program_invocations_with,
+};
+use solana_pumpfun::price::SolPriceFeed;
+use solana_pumpfun::trade::{AutoBuyer, BuyLimits, BuyParams};
+use solana_pumpfun::utils::{OutputFormat, TransactionPretty};
+use solana_pumpfun::verify::MintVerifier;
+use solana_pumpfun::wallet::FreshWalletChecker;
+
+use crate::aggregate::TradeAggregator;
+use crate::buyers::FirstBuyers;
+use crate::cli::StreamArgs;
+use crate::control::ControlEvent;
+use crate::copycat::CopycatDetector;
+use crate::cursor::{self, Cursor};
+use crate::dedup::LaunchDedup;
+use crate::display::PubkeyStyle;
+use crate::filter::LaunchFilter;
+use crate::gate::LaunchGate;
+use crate::projection::FieldProjection;
+use crate::reuse::MetadataReuse;
+use crate::sampler::LaunchSampler;
+use crate::sinks::file::FileSink;
+#[cfg(feature = "nats")]
+use crate::sinks::nats::NatsSink;
+#[cfg(feature = "pubsub")]
+use crate::sinks::pubsub::PubSubSink;
+use crate::sinks::redis::RedisSink;
+#[cfg(feature = "sns")]
+use crate::sinks::sns::SnsSink;
+use crate::sinks::stdout::StdoutSink;
+use crate::sinks::webhook::WebhookSink;
+use crate::sinks::{SinkHandle, SinkSpec, spawn_sink};
+use crate::stats::SessionStats;
+use crate::surge::SurgeDetector;
+use crate::template::LaunchTemplate;
+use crate::transform::{Transform, TransformWorker, Transformed};
+
+// launches buffered per sink while it is slow or reconnecting
+const SINK_BUFFER: usize = 10_000;
+const SINK_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+// the log file is the record of the session, give its backlog longer
+const FILE_CLOSE_TIMEOUT: Duration = Duration::from_secs(30);
+// how long shutdown waits on enrichment still in flight, at least the finality timeout
+const ENRICH_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+// how long shutdown waits on launches still queued for `--transform`
+const TRANSFORM_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+// how often held launches are checked against `--gate-timeout`
+const GATE_EXPIRY_INTERVAL: Duration = Duration::from_secs(1);
+
+// per-launch processing state for one stream session
+pub struct Pipeline {
+    pub parse_options: ParseOptions,
+    // `--program-id`, whose events are parsed from each transaction
+    pub programs: Vec<Pubkey>,
+    pub filter: LaunchFilter,
+    pub stats: SessionStats,
+    pub auto_buyer: Option<Arc<AutoBuyer>>,
+    pub sinks: Vec<SinkHandle>,
+    // `--trades-for`: when set, print these mints' trades instead of launches
+    pub trades_for: HashSet<Pubkey>,
+    pub enrichment: Option<Enrichment>,
+    // the `--output` log, written off the runtime; None with `--no-log`, or
+    // `--tail`, which reads a log
+    file: Option<SinkHandle>,
+    pub fields: Option<FieldProjection>,
+    pub copycats: Option<CopycatDetector>,
+    // `metadata_reuse_count`, with `--fetch-metadata` or `--flag-metadata-reuse`
+    pub metadata_reuse: Option<MetadataReuse>,
+    pub drop_unverified: bool,
+    pub drop_unfinalized: bool,
+    pub require_complete_metadata: bool,
+    pub print_raw: bool,
+    pub tally_instructions: bool,
+    // `--quiet`: no launch printout, warnings and errors still go to the log
+    pub quiet: bool,
+    pub pubkeys: PubkeyStyle,
+    pub template: Option<LaunchTemplate>,
+    pub link_template: String,
+    pub commitment: Option<Commitment>,
+    pub sol_price: Option<SolPriceFeed>,
+    // `--trade-aggregate-window`: trades are summarized per mint each tick instead of printed
+    trade_aggregate: Option<(Interval, TradeAggregator)>,
+    // `--summary-interval`
+    summary_interval: Option<Interval>,
+    // `--max-per-second`
+    sampler: Option<LaunchSampler>,
+    // `--dedup-window`, None with `0s`
+    dedup: Option<LaunchDedup>,
+    // `--require-first-trade` / `--min-launches-to-write`, expired each tick
+    gate: Option<(Interval, LaunchGate)>,
+    // `--first-buyers`, windows expired each tick
+    first_buyers: Option<(Interval, FirstBuyers)>,
+    // `--cursor-file`, written each tick
+    cursor: Option<(Interval, Cursor)>,
+    // `--surge-multiplier`, checked once a minute
+    surge: Option<(Interval, SurgeDetector)>,
+    // `--transform`, run on each launch as it's committed and answered through `tick`
+    transform: Option<TransformWorker>,
+}
+
+// launches waiting on `--fetch-metadata`, `--verify-mint-onchain` or
+// `--confirm-finality` come back through `rx` to be persisted
+pub struct Enrichment {
+    fetcher: Option<MetadataFetcher>,
+    verifier: Option<Arc<MintVerifier>>,
+    finality: Option<Arc<FinalityChecker>>,
+    wallets: Option<FreshWalletChecker>,
+    tx: mpsc::UnboundedSender<(CreateTokenInfo, Span)>,
+    rx: mpsc::UnboundedReceiver<(CreateTokenInfo, Span)>,
+}
+
+impl Pipeline {
+    pub fn from_args(args: &StreamArgs) -> anyhow::Result<Self> {
+        let auto_buyer = if args.auto_buy {
+            // clap enforces these together with --i-understand-the-risk
+            let keypair_path = args.keypair_path.as_deref().unwrap_or_default();
+            let auto_buyer = AutoBuyer::new(
+                &args.rpc_url,
+                keypair_path,
+                BuyParams {
+                    sol_amount: args.buy_sol_amount.unwrap_or_default(),
+                    slippage_bps: args.slippage_bps,
+                    priority_fee: args.priority_fee,
+                    compute_unit_limit: args.compute_unit_limit,
+                },
+                BuyLimits {
+                    max_buys: args.max_buys,
+                    max_spend_sol: args.max_spend_sol,
+                },
+            )?;
+            println!(
+                "Auto-buy enabled: {} SOL per launch from {}, up to {} buys / {} SOL",
+                args.buy_sol_amount.unwrap_or_default(),
+                auto_buyer.wallet(),
+                args.max_buys
+                    .map_or("unlimited".to_string(), |max| max.to_string()),
+                args.max_spend_sol
+                    .map_or("unlimited".to_string(), |max| max.to_string()),
+            );
+            Some(Arc::new(auto_buyer))
+        } else {
+            None
+        };
+
+        if args.fields.is_some() && args.format == OutputFormat::Borsh {
+            anyhow::bail!("--fields only applies to JSON output, not --format borsh");
+        }
+
+        let mut sinks = vec![];
+        if let Some(redis_url) = &args.redis_url {
+            let redis = RedisSink::new(redis_url, &args.redis_channel, args.fields.clone())?;
+            sinks.push(spawn_sink(redis, SINK_BUFFER, args.batch_policy("redis")));
+        }
+        #[cfg(feature = "nats")]
+        if let Some(nats_url) = &args.nats_url {
+            let nats = NatsSink::new(nats_url, &args.nats_subject, args.fields.clone())?;
+            sinks.push(spawn_sink(nats, SINK_BUFFER, args.batch_policy("nats")));
+        }
+        #[cfg(feature = "pubsub")]
+        if let Some(topic) = &args.pubsub_topic {
+            let pubsub = PubSubSink::new(topic, args.fields.clone())?;
+            sinks.push(spawn_sink(pubsub, SINK_BUFFER, args.batch_policy("pubsub")));
+        }
+        #[cfg(feature = "sns")]
+        if let Some(topic_arn) = &args.sns_topic_arn {
+            let sns = SnsSink::new(topic_arn, args.fields.clone());
+            sinks.push(spawn_sink(sns, SINK_BUFFER, args.batch_policy("sns")));
+        }
+
+        for spec in &args.sink {
+            sinks.push(match spec {
+                SinkSpec::StdoutJsonl => spawn_sink(
+                    StdoutSink::new(args.fields.clone()),
+                    SINK_BUFFER,
+                    args.batch_policy("stdout"),
+                ),
+                SinkSpec::File(path) => spawn_sink(
+                    FileSink::extra(path, args),
+                    SINK_BUFFER,
+                    args.batch_policy("file"),
+                ),
+                SinkSpec::Webhook(url) => spawn_sink(
+                    WebhookSink::new(url, args.fields.clone()),
+                    SINK_BUFFER,
+                    args.batch_policy("webhook"),
+                ),
+            });
+        }
+
+        let enrichment = if args.fetch_metadata
+            || args.verify_mint_onchain
+            || args.confirm_finality
+            || args.check_fresh_wallet
+        {
+            let (tx, rx) = mpsc::unbounded_channel();
+            Some(Enrichment {
+                fetcher: args
+                    .fetch_metadata
+                    .then(|| MetadataFetcher::new(args.metadata_concurrency))
+                    .transpose()?,
+                verifier: args
+                    .verify_mint_onchain
+                    .then(|| Arc::new(MintVerifier::new(&args.rpc_url))),
+                finality: args
+                    .confirm_finality
+                    .then(|| Arc::new(FinalityChecker::new(&args.rpc_url, args.finality_timeout))),
+                wallets: args.check_fresh_wallet.then(|| {
+                    FreshWalletChecker::new(
+                        &args.rpc_url,
+                        args.fresh_wallet_max_txs,
+                        args.fresh_wallet_max_age,
+                        args.metadata_concurrency,
+                    )
+                }),
+                tx,
+                rx,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            parse_options: ParseOptions {
+                allow_partial: args.allow_partial,
+                max_field_len: args.max_field_len,
+            },
+            programs: args.program_ids.clone(),
+            // parse bounds once, relative durations are anchored at startup
+            filter: LaunchFilter::from_args(args)?,
+            stats: SessionStats::new(args.summary_top),
+            auto_buyer,
+            sinks,
+            trades_for: args.trades_for.iter().copied().collect(),
+            enrichment,
+            file: (args.tail.is_none() && !args.no_log).then(|| {
+                spawn_sink(
+                    FileSink::from_args(args),
+                    SINK_BUFFER,
+                    args.batch_policy("file"),
+                )
+            }),
+            fields: args.fields.clone(),
+            copycats: args.copycat_threshold.map(CopycatDetector::new),
+            metadata_reuse: (args.fetch_metadata || args.flag_metadata_reuse.is_some())
+                .then(|| MetadataReuse::new(args.flag_metadata_reuse)),
+            drop_unverified: args.drop_unverified,
+            require_complete_metadata: args.require_complete_metadata,
+            drop_unfinalized: args.drop_unfinalized,
+            print_raw: args.print_raw,
+            tally_instructions: args.tally_instructions,
+            quiet: args.quiet,
+            pubkeys: args.pubkey_abbrev.unwrap_or_default(),
+            template: args.template.clone(),
+            link_template: args.link_template.clone(),
+            commitment: args.commitment,
+            sol_price: args
+                .sol_price_source
+                .as_deref()
+                .map(|url| {
+                    SolPriceFeed::spawn(
+                        url,
+                        args.sol_price_pointer.as_deref(),
+                        args.sol_price_refresh,
+                    )
+                })
+                .transpose()?,
+            trade_aggregate: args
+                .trade_aggregate_window
+                .map(|window| (delayed_interval(window), TradeAggregator::default())),
+            summary_interval: args.summary_interval.map(delayed_interval),
+            sampler: args.max_per_second.map(LaunchSampler::new),
+            dedup: (!args.dedup_window.is_zero()).then(|| LaunchDedup::new(args.dedup_window)),
+            gate: LaunchGate::from_args(args)
+                .map(|gate| (delayed_interval(GATE_EXPIRY_INTERVAL), gate)),
+            first_buyers: FirstBuyers::from_args(args)
+                .map(|first_buyers| (delayed_interval(GATE_EXPIRY_INTERVAL), first_buyers)),
+            cursor: args.cursor_file.as_deref().map(|path| {
+                let interval = cursor::write_interval(args.flush_interval);
+                (delayed_interval(interval), Cursor::new(path))
+            }),
+            surge: SurgeDetector::from_args(args).map(|surge| (SurgeDetector::interval(), surge)),
+            transform: args
+                .transform
+                .as_deref()
+                .map(|command| Transform::spawn(command, args.transform_timeout))
+                .transpose()?
+                .map(TransformWorker::spawn),
+        })
+    }
+
+    /// Waits for background work between updates: persisting a launch back
+    /// from enrichment or `--transform`, or a periodic printout. Pending forever when none is enabled.
+    pub async fn tick(&mut self) {
+        let work = {
+            let enrichment = &mut self.enrichment;
+            let transform = &mut self.transform;
+            let trade_aggregate = &mut self.trade_aggregate;
+            let summary_interval = &mut self.summary_interval;
+            let gate = &mut self.gate;
+            let first_buyers = &mut self.first_buyers;
+            let cursor = &mut self.cursor;
+            let surge = &mut self.surge;
+            tokio::select! {
+                Some(enriched) = next_enriched(enrichment) => Background::Enriched(Box::new(enriched)),
+                Some(transformed) = next_transformed(transform) => {
+                    Background::Transformed(Box::new(transformed))
+                }
+                _ = next_interval(trade_aggregate.as_mut().map(|(interval, _)| interval)) => {
+                    Background::TradeSummary
+                }
+                _ = next_interval(summary_interval.as_mut()) => Background::Summary,
+                _ = next_interval(gate.as_mut().map(|(interval, _)| interval)) => {
+                    Background::GateExpiry
+                }
+                _ = next_interval(first_buyers.as_mut().map(|(interval, _)| interval)) => {
+                    Background::FirstBuyersExpiry
+                }
+                _ = next_interval(cursor.as_mut().map(|(interval, _)| interval)) => {
+                    Background::Cursor
+                }
+                _ = next_interval(surge.as_mut().map(|(interval, _)| interval)) => {
+                    Background::Surge
+                }
+            }
+        };
+
+        match work {
+            Background::Enriched(enriched) => {
+                let (token_info, span) = *enriched;
+                span.in_scope(|| self.persist_enriched(token_info))
+            }
+            Background::Transformed(transformed) => self.persist_transformed(*transformed),
+            Background::TradeSummary => self.print_trade_summaries(),
+            Background::Summary => self.stats.print_window(),
+            Background::GateExpiry => self.expire_held(),
+            Background::FirstBuyersExpiry => {
+                let closed = match &mut self.first_buyers {
+                    Some((_, first_buyers)) => first_buyers.expire(),
+                    None => vec![],
+                };
+                for (token_info, span) in closed {
+                    span.in_scope(|| self.admit(&token_info));
+                }
+            }
+            Background::Cursor => {
+                if let Some((_, cursor)) = &mut self.cursor {
+                    cursor.write_settled();
+                }
+            }
+            Background::Surge => {
+                if let Some((_, surge)) = &mut self.surge
+                    && let Some(event) = surge.check()
+                {
+                    self.send_control(event);
+                }
+            }
+        }
+    }
+
+    /// Advances `--cursor-file` past an update the pipeline is done with.
+    pub fn record_processed(&mut self, slot: u64) {
+        if let Some((_, cursor)) = &mut self.cursor {
+            cursor.observe(slot);
+        }
+    }
+
+    // `--cursor-file` stays at a launch's slot from when it passes the
+    // filters until it's committed or dropped
+    fn hold_slot(&mut self, slot: u64) {
+        if let Some((_, cursor)) = &mut self.cursor {
+            cursor.hold(slot);
+        }
+    }
+
+    fn release_slot(&mut self, slot: u64) {
+        if let Some((_, cursor)) = &mut self.cursor {
+            cursor.release(slot);
+        }
+    }
+
+    fn expire_held(&mut self) {
+        let Some((_, gate)) = &mut self.gate else {
+            return;
+        };
+        let timeout = gate.timeout();
+        for token_info in gate.expire() {
+            debug!(
+                "Dropping {}: held for {:?} without meeting the launch gate",
+                token_info.mint, timeout
+            );
+            self.stats.gated_out += 1;
+            self.release_slot(token_info.slot);
+        }
+    }
+
+    fn print_trade_summaries(&mut self) {
+        let Some((_, aggregator)) = &mut self.trade_aggregate else {
+            return;
+        };
+        for (mint, summary) in aggregator.drain() {
+            println!(
+                "{} buys {} sells {} net {:+.4} SOL last {:.10} SOL",
+                self.pubkeys.show(&mint),
+                summary.buys,
+                summary.sells,
+                summary.net_sol(),
+                summary.last_price_sol,
+            );
+        }
+    }
+
+    /// Waits briefly for in-flight enrichment, then flushes the log file and
+    /// what the sinks still have buffered.
+    pub async fn close(mut self) {
+        if let Some(enrichment) = self.enrichment.take() {
+            // outstanding fetches hold their own senders, so `recv` ends with the last
+            let Enrichment {
+                tx,
+                mut rx,
+                finality,
+                ..
+            } = enrichment;
+            drop(tx);
+            // a launch may still be waiting out its whole finality timeout
+            let drain_timeout = finality.map_or(ENRICH_DRAIN_TIMEOUT, |finality| {
+                ENRICH_DRAIN_TIMEOUT.max(finality.timeout())
+            });
+            let drain = async {
+                while let Some((token_info, span)) = rx.recv().await {
+                    span.in_scope(|| self.persist_enriched(token_info));
+                }
+            };
+            if tokio::time::timeout(drain_timeout, drain).await.is_err() {
+                warn!("Gave up waiting on launch enrichment still in flight");
+            }
+        }
+        // short of buyers, but otherwise done
+        let held = match &mut self.first_buyers {
+            Some((_, first_buyers)) => first_buyers.drain(),
+            None => vec![],
+        };
+        for (token_info, span) in held {
+            span.in_scope(|| self.admit(&token_info));
+        }
+        if let Some(mut transform) = self.transform.take() {
+            transform.close();
+            let drain = async {
+                while let Some(answer) = transform.recv().await {
+                    self.persist_transformed(answer);
+                }
+            };
+            if tokio::time::timeout(TRANSFORM_DRAIN_TIMEOUT, drain)
+                .await
+                .is_err()
+            {
+                warn!("Gave up waiting on --transform for the launches still queued");
+            }
+        }
+        // left held in the cursor, a restart replays them against new trades
+        if let Some((_, gate)) = &self.gate
+            && gate.held() > 0
+        {
+            info!(
+                "Dropping {} launches still held by the launch gate",
+                gate.held()
+            );
+        }
+        let mut failures = vec![];
+        for sink in self.sinks {
+            failures.push(sink.close(SINK_CLOSE_TIMEOUT).await);
+        }
+        if let Some(file) = self.file {
+            failures.push(file.close(FILE_CLOSE_TIMEOUT).await);
+        }
+        let failures: Vec<String> = failures
+            .into_iter()
+            .filter(|(_, failures)| *failures > 0)
+            .map(|(name, failures)| format!("{} {}", name, failures))
+            .collect();
+        if !failures.is_empty() {
+            println!("Sink Failures: {}", failures.join(", "));
+        }
+        if let Some((_, cursor)) = self.cursor {
+            cursor.close();
+        }
+    }
+
+    /// Runs a transaction update through the pipeline, other updates are ignored.
+    pub async fn process_update(&mut self, update: SubscribeUpdate) -> anyhow::Result<()> {
+        let Some(UpdateOneof::Transaction(subscribe_update_tx)) = update.update_oneof else {
+            return Ok(());
+        };
+
+        // some providers/filters send transactions without meta, leaving no logs to parse
+        if subscribe_update_tx
+            .transaction
+            .as_ref()
+            .is_none_or(|tx| tx.meta.is_none())
+        {
+            self.skip_missing_meta(subscribe_update_tx.slot);
+            return Ok(());
+        }
+
+        // one trace per update: decode, parse, persist, then sinks and enrichment
+        let span = info_span!("receive", slot = subscribe_update_tx.slot);
+        let slot = subscribe_update_tx.slot;
+        let decoded = info_span!(parent: &span, "decode")
+            .in_scope(|| TransactionPretty::try_from(subscribe_update_tx));
+        let mut transaction_pretty = match decoded {
+            Ok(transaction_pretty) => transaction_pretty,
+            Err(e) => {
+                self.stats.invalid_updates += 1;
+                warn!("Skipping update in slot {}: {}", slot, e);
+                return Ok(());
+            }
+        };
+        transaction_pretty.provider_created_at = update
+            .created_at
+            .and_then(|ts| DateTime::from_timestamp(ts.seconds, ts.nanos as u32));
+
+        self.process_tx_update(transaction_pretty)
+            .instrument(span)
+            .await
+    }
+
+    #[tracing::instrument(name = "process", skip_all, fields(slot = transaction_pretty.slot))]
+    pub async fn process_tx_update(
+        &mut self,
+        transaction_pretty: TransactionPretty,
+    ) -> anyhow::Result<()> {
+        let detected_at = Utc::now();
+        let trade_raw = &transaction_pretty.tx;
+        let Some(meta) = trade_raw.meta.as_ref() else {
+            self.skip_missing_meta(transaction_pretty.slot);
+            return Ok(());
+        };
+
+        if meta.err.is_some() {
+            return Ok(());
+        }
+
+        let logs = if let OptionSerializer::Some(logs) = &meta.log_messages {
+            logs
+        } else {
+            &vec![]
+        };
+
+        if self.tally_instructions {
+            for name in parse_instruction_names(logs) {
+                self.stats.record_instruction(name);
+            }
+        }
+
+        if !self.trades_for.is_empty() {
+            self.print_trades(logs, transaction_pretty.slot)?;
+            return Ok(());
+        }
+
+        let invocations = info_span!("parse").in_scope(|| {
+            parse_program_invocations_with(logs, &self.programs, &self.parse_options)
+        })?;
+
+        // trades first, so the creator's own buy in the launch transaction doesn't count
+        if self.gate.is_some() || self.first_buyers.is_some() {
+            let mut released = vec![];
+            let mut closed = vec![];
+            for invocation in &invocations {
+                let PumpfunEvent::Trade(trade_info) = &invocation.event else {
+                    continue;
+                };
+                if let Some((_, first_buyers)) = &mut self.first_buyers {
+                    closed.extend(first_buyers.observe_trade(trade_info));
+                }
+                if let Some((_, gate)) = &mut self.gate {
+                    released.extend(gate.observe_trade(&trade_info.mint));
+                }
+            }
+            for (token_info, span) in released {
+                span.in_scope(|| self.commit(&token_info));
+            }
+            for (token_info, span) in closed {
+                span.in_scope(|| self.admit(&token_info));
+            }
+        }
+
+        for invocation in invocations {
+            let mut token_info = match invocation.event {
+                PumpfunEvent::Create(token_info) => token_info,
+                PumpfunEvent::AmmCreatePool(pool_info) if !self.quiet => {
+                    print_pool(&pool_info, transaction_pretty.slot, self.pubkeys);
+                    continue;
+                }
+                PumpfunEvent::AmmSwap(swap_info) if !self.quiet => {
+                    print_swap(&swap_info, transaction_pretty.slot, self.pubkeys);
+                    continue;
+                }
+                _ => continue,
+            };
+            if let Some(dedup) = &mut self.dedup
+                && dedup.is_duplicate(token_info.mint, transaction_pretty.signature)
+            {
+                debug!(
+                    "Skipping {}: already seen in {}",
+                    token_info.mint, transaction_pretty.signature
+                );
+                self.stats.duplicates += 1;
+                continue;
+            }
+            if !self.filter.matches(&token_info) {
+                continue;
+            }
+            if let Some(sampler) = &mut self.sampler {
+                if !sampler.admit() {
+                    self.stats.record_sampled_out();
+                    continue;
+                }
+                token_info.sampled = true;
+            }
+
+            token_info.slot = transaction_pretty.slot;
+            token_info.tx_index = transaction_pretty.index;
+            token_info.commitment = self.commitment;
+            token_info.initial_market_cap_usd = self
+                .sol_price
+                .as_ref()
+                .and_then(SolPriceFeed::latest)
+                .map(|sol_usd| sol_usd * token_info.initial_market_cap_sol());
+            token_info.creator_initial_sol_spent = transaction_pretty
+                .lamports_spent(&token_info.user)
+                .map(|lamports| lamports as f64 / LAMPORTS_PER_SOL);
+            token_info.detection_latency_ms = detection_latency_ms(
+                detected_at,
+                transaction_pretty.provider_created_at,
+                token_info.block_time,
+            );
+            if let Some(copycats) = &mut self.copycats {
+                token_info.similar_to = copycats.observe(&token_info);
+            }
+
+            self.stats.record_launch(&token_info);
+            if let Some((_, surge)) = &mut self.surge {
+                surge.record_launch();
+            }
+            if let Some(latency_ms) = token_info.detection_latency_ms {
+                self.stats.detection_latency.observe(latency_ms);
+            }
+
+            if !self.quiet {
+                self.print_launch_details(
+                    &token_info,
+                    &logs[invocation.logs.clone()],
+                    &invocation.program_data,
+                    &transaction_pretty.signature,
+                    transaction_pretty.slot,
+                );
+            }
+
+            if let Some(auto_buyer) = &self.auto_buyer
+                && !token_info.partial
+            {
+                if auto_buyer.reserve() {
+                    spawn_auto_buy(auto_buyer.clone(), token_info.clone());
+                } else {
+                    let (buys, sol) = auto_buyer.committed();
+                    warn!(
+                        "Auto-buy limit reached after {} buys ({:.4} SOL), not buying any more",
+                        buys, sol
+                    );
+                    self.auto_buyer = None;
+                }
+            }
+
+            // trades from here on count, while the launch is enriched or held
+            if let Some((_, gate)) = &mut self.gate {
+                gate.watch(token_info.mint);
+            }
+            if let Some((_, first_buyers)) = &mut self.first_buyers {
+                first_buyers.watch(token_info.mint);
+            }
+            self.hold_slot(token_info.slot);
+            match &self.enrichment {
+                Some(enrichment) => {
+                    spawn_enrichment(enrichment, token_info, transaction_pretty.signature)
+                }
+                None => {
+                    if let Some(metadata_reuse) = &mut self.metadata_reuse {
+                        metadata_reuse.observe(&mut token_info);
+                    }
+                    self.persist(&token_info)
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_launch_details(
+        &self,
+        token_info: &CreateTokenInfo,
+        raw_logs: &[String],
+        program_data: &str,
+        signature: &Signature,
+        slot: u64,
+    ) {
+        if let Some(template) = &self.template {
+            println!("{}", template.render(token_info, signature, self.pubkeys));
+            if self.print_raw {
+                print_raw(raw_logs, program_data);
+            }
+            return;
+        }
+
+        // print to console
+        match &self.fields {
+            Some(fields) => {
+                println!(
+                    "New Pumpfun Launch:\n{}\n",
+                    fields.describe(token_info, self.pubkeys)
+                )
+            }
+            None => print_launch(token_info, slot, self.pubkeys),
+        }
+        println!(
+            "Explorer: {}\n",
+            self.link_template
+                .replace("{signature}", &signature.to_string())
+        );
+
+        if let Some(market_cap_usd) = token_info.initial_market_cap_usd {
+            println!("Initial Market Cap: ${:.0}\n", market_cap_usd);
+        }
+        if let Some(sol_spent) = token_info.creator_initial_sol_spent {
+            println!("Creator Spent: {:.4} SOL\n", sol_spent);
+        }
+        if let Some(dev_buy_sol) = token_info.dev_buy_sol {
+            println!("Dev Buy: {:.4} SOL\n", dev_buy_sol);
+        }
+        if token_info.partial {
+            println!("Partial record: event data was truncated\n");
+        }
+        if let Some(similar_to) = token_info.similar_to {
+            println!("Possible copycat of {}\n", self.pubkeys.show(&similar_to));
+        }
+        if self.print_raw {
+            print_raw(raw_logs, program_data);
+        }
+        println!("---");
+    }
+
+    // expected with some providers' defaults, so counted rather than logged as an error
+    fn skip_missing_meta(&mut self, slot: u64) {
+        self.stats.missing_meta += 1;
+        debug!("Skipping transaction without meta in slot {}", slot);
+    }
+
+    /// Persists a launch back from enrichment, unless `--drop-unverified`,
+    /// `--drop-unfinalized` or `--require-complete-metadata` rejects it.
+    pub fn persist_enriched(&mut self, mut token_info: CreateTokenInfo) {
+        if self.drop_unverified && token_info.mint_verified == Some(false) {
+            warn!("Dropping {}: mint not found on-chain", token_info.mint);
+            self.release_slot(token_info.slot);
+            return;
+        }
+        if self.drop_unfinalized && token_info.finalized == Some(false) {
+            warn!("Dropping {}: launch did not finalize", token_info.mint);
+            self.release_slot(token_info.slot);
+            return;
+        }
+        if self.require_complete_metadata
+            && token_info
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.image.as_deref())
+                .is_none_or(|image| image.trim().is_empty())
+        {
+            info!("Dropping {}: metadata has no image", token_info.mint);
+            self.release_slot(token_info.slot);
+            return;
+        }
+
+        // counted once the metadata is in, it's the better key
+        if let Some(metadata_reuse) = &mut self.metadata_reuse {
+            metadata_reuse.observe(&mut token_info);
+        }
+        self.persist(&token_info);
+    }
+
+    /// Passes a finished launch on, once `--first-buyers` has its buyers.
+    pub fn persist(&mut self, token_info: &CreateTokenInfo) {
+        let Some((_, first_buyers)) = &mut self.first_buyers else {
+            self.admit(token_info);
+            return;
+        };
+        if let Some((token_info, span)) = first_buyers.hold(token_info) {
+            span.in_scope(|| self.admit(&token_info));
+        }
+    }
+
+    /// Commits a launch, or hands it to the launch gate to hold until its
+    /// conditions are met.
+    fn admit(&mut self, token_info: &CreateTokenInfo) {
+        let Some((_, gate)) = &mut self.gate else {
+            self.commit(token_info);
+            return;
+        };
+        for (token_info, span) in gate.admit(token_info) {
+            span.in_scope(|| self.commit(&token_info));
+        }
+    }
+
+    /// Sends a launch to every sink and the log file, once `--transform`
+    /// has had its say.
+    fn commit(&mut self, token_info: &CreateTokenInfo) {
+        if let Some(transform) = &self.transform {
+            // comes back through `tick`, its slot held until then
+            transform.send(token_info.clone(), Span::current());
+            return;
+        }
+        self.publish(token_info.slot, token_info);
+    }
+
+    fn persist_transformed(&mut self, answer: Transformed) {
+        let Transformed {
+            token_info,
+            transformed,
+            span,
+        } = answer;
+        let _span = span.entered();
+        match transformed {
+            Some(transformed) => self.publish(token_info.slot, &transformed),
+            None => {
+                debug!("Dropping {}: --transform returned null", token_info.mint);
+                self.release_slot(token_info.slot);
+            }
+        }
+    }
+
+    // `slot` is the one the launch came from, whatever --transform made of it
+    fn publish(&mut self, slot: u64, token_info: &CreateTokenInfo) {
+        let _span = info_span!("persist", mint = %token_info.mint).entered();
+        self.release_slot(slot);
+        for sink in &self.sinks {
+            sink.send(token_info);
+        }
+        if let Some(file) = &self.file {
+            file.send(token_info);
+        }
+    }
+
+    /// Sends a stream-level event to every sink, behind the launches
+    /// already queued. The log file only ever holds launches.
+    pub fn send_control(&self, event: ControlEvent) {
+        info!("Stream event: {}", event.kind());
+        for sink in &self.sinks {
+            sink.send_control(&event);
+        }
+    }
+
+    /// Sends a record read back from a log through the filter and sinks.
+    pub fn replay(&mut self, token_info: &CreateTokenInfo) {
+        if !self.filter.matches(token_info) {
+            return;
+        }
+        self.stats.record_launch(token_info);
+        if !self.quiet {
+            println!(
+                "Replaying {} ({}) from slot {}",
+                self.pubkeys.show(&token_info.mint),
+                token_info.symbol,
+                token_info.slot
+            );
+        }
+        self.persist(token_info);
+    }
+
+    fn print_trades(&mut self, logs: &[String], slot: u64) -> anyhow::Result<()> {
+        for event in parse_events_with(logs, &self.parse_options)? {
+            if let PumpfunEvent::Trade(trade_info) = event
+                && self.trades_for.contains(&trade_info.mint)
+            {
+                match &mut self.trade_aggregate {
+                    Some((_, aggregator)) => aggregator.observe(&trade_info),
+                    None => print_trade(&trade_info, slot, self.pubkeys),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn print_launch(token_info: &CreateTokenInfo, slot: u64, pubkeys: PubkeyStyle) {
+    println!(
+        "New Pumpfun Launch:\n\
+    Token Address: {}\n\
+    Bonding Curve Address: {}\n\
+    Name: {}\n\
+    Symbol: {}\n\
+    Owner: {}\n\
+    Slot: {}\n\
+    Pumpfun Invokes: {} (max depth {})\n\
+    Latency: {}\n\
+    ",
+        pubkeys.show(&token_info.mint),
+        pubkeys.show(&token_info.bonding_curve),
+        token_info.name,
+        token_info.symbol,
+        pubkeys.show(&token_info.user),
+        slot,
+        token_info.invoke_count,
+        token_info.max_invoke_depth,
+        token_info
+            .detection_latency_ms
+            .map_or("n/a".to_string(), |ms| format!("{} ms", ms))
+    );
+}
+
+fn print_raw(logs: &[String], program_data: &str) {
+    println!("Raw logs:");
+    for log in logs {
+        println!("  {}", log);
+    }
+    println!("Raw program data: {}\n", program_data);
+}
+
+// one tape line per trade
+fn print_trade(trade_info: &TradeInfo, slot: u64, pubkeys: PubkeyStyle) {
+    println!(
+        "{} {} {:.4} SOL for {:.2} tokens @ {:.10} SOL  user {}  slot {}",
+        if trade_info.is_buy { "BUY " } else { "SELL" },
+        pubkeys.show(&trade_info.mint),
+        trade_info.sol(),
+        trade_info.tokens(),
+        trade_info.price_sol(),
+        pubkeys.show(&trade_info.user),
+        slot,
+    );
+}
+
+// a migration, the launch's mint is the pool's base
+fn print_pool(pool_info: &AmmPoolInfo, slot: u64, pubkeys: PubkeyStyle) {
+    println!(
+        "MIGRATED {} to pool {} ({:.4} SOL, {} tokens)  creator {}  slot {}",
+        pubkeys.show(&pool_info.base_mint),
+        pubkeys.show(&pool_info.pool),
+        pool_info.quote_sol(),
+        pool_info.base_amount_in,
+        pubkeys.show(&pool_info.creator),
+        slot,
+    );
+}
+
+fn print_swap(swap_info: &AmmSwapInfo, slot: u64, pubkeys: PubkeyStyle) {
+    println!(
+        "AMM {} pool {} {:.4} SOL for {} tokens  user {}  slot {}",
+        if swap_info.is_buy { "BUY " } else { "SELL" },
+        pubkeys.show(&swap_info.pool),
+        swap_info.quote_sol(),
+        swap_info.base_amount,
+        pubkeys.show(&swap_info.user),
+        slot,
+    );
+}
+
+fn delayed_interval(period: Duration) -> Interval {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
+async fn next_enriched(enrichment: &mut Option<Enrichment>) -> Option<(CreateTokenInfo, Span)> {
+    match enrichment {
+        Some(enrichment) => enrichment.rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn next_transformed(transform: &mut Option<TransformWorker>) -> Option<Transformed> {
+    match transform {
+        Some(transform) => transform.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn next_interval(interval: Option<&mut Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+enum Background {
+    Enriched(Box<(CreateTokenInfo, Span)>),
+    Transformed(Box<Transformed>),
+    TradeSummary,
+    Summary,
+    GateExpiry,
+    FirstBuyersExpiry,
+    Cursor,
+    Surge,
+}
+
+// the launch is persisted once enrichment finishes, with whatever succeeded
+fn spawn_enrichment(
+    enrichment: &Enrichment,
+    mut token_info: CreateTokenInfo,
+    signature: Signature,
+) {
+    let fetcher = enrichment.fetcher.clone();
+    let verifier = enrichment.verifier.clone();
+    let finality = enrichment.finality.clone();
+    let wallets = enrichment.wallets.clone();
+    let tx = enrichment.tx.clone();
+    let span = Span::current();
+    let enrich = async move {
+        if let Some(fetcher) = fetcher {
+            let fetched = fetcher
+                .fetch(&token_info.uri)
+                .instrument(info_span!("metadata"))
+                .await;
+            match fetched {
+                Ok(metadata) => token_info.metadata = Some(metadata),
+                Err(e) => warn!("Metadata fetch for {} failed: {:?}", token_info.mint, e),
+            }
+        }
+        if let Some(verifier) = verifier {
+            let verified = verifier
+                .verify(&token_info.mint)
+                .instrument(info_span!("verify_mint"))
+                .await;
+            match verified {
+                Ok(verified) => {
+                    if !verified {
+                        warn!("Mint {} failed on-chain verification", token_info.mint);
+                    }
+                    token_info.mint_verified = Some(verified);
+                }
+                Err(e) => warn!("Mint verification for {} failed: {:?}", token_info.mint, e),
+            }
+        }
+        if let Some(wallets) = wallets {
+            let fresh = wallets
+                .is_fresh(&token_info.user)
+                .instrument(info_span!("fresh_wallet"))
+                .await;
+            match fresh {
+                Ok(fresh) => token_info.fresh_wallet = Some(fresh),
+                Err(e) => warn!("Wallet check for {} failed: {:?}", token_info.user, e),
+            }
+        }
+        if let Some(finality) = finality {
+            let finalized = finality
+                .wait_finalized(&signature)
+                .instrument(info_span!("confirm_finality"))
+                .await;
+            match finalized {
+                Ok(finalized) => {
+                    if !finalized {
+                        warn!(
+                            "Launch {} not finalized within {:?}",
+                            token_info.mint,
+                            finality.timeout()
+                        );
+                    }
+                    token_info.finalized = Some(finalized);
+                }
+                Err(e) => warn!("Finality check for {} failed: {:?}", token_info.mint, e),
+            }
+        }
+        let _ = tx.send((token_info, Span::current()));
+    };
+    tokio::spawn(enrich.instrument(info_span!(parent: &span, "enrich")));
+}
+
+// from the provider's timestamp when it sends one, else the block time,
+// which only has whole seconds
+fn detection_latency_ms(
+    detected_at: DateTime<Utc>,
+    provider_created_at: Option<DateTime<Utc>>,
+    block_time: Option<i64>,
+) -> Option<i64> {
+    let created_at = provider_created_at
+        .or_else(|| block_time.and_then(|block_time| DateTime::from_timestamp(block_time, 0)))?;
+    Some((detected_at - created_at).num_milliseconds())
+}
+
+// buys run off the stream loop so a slow RPC never delays detection
+fn spawn_auto_buy(auto_buyer: Arc<AutoBuyer>, token_info: CreateTokenInfo) {
+    tokio::spawn(async move {
+        match auto_buyer.buy(&token_info).await {
+            Ok(signature) => info!("Auto-buy of {} sent: {}", token_info.mint, signature),
+            Err(e) => error!("Auto-buy of {} failed: {:?}", token_info.mint, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use yellowstone_grpc_proto::prelude::{
+        SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo, TransactionStatusMeta,
+    };
+
+    use super::*;
+    use crate::recording::{Recorder, RecordingReader};
+
+    fn pipeline(flags: &[&str]) -> Pipeline {
+        let args = ["solana-pumpfun", "stream", "--no-log", "--quiet"]
+            .iter()
+            .chain(flags);
+        match crate::cli::Cli::try_parse_from(args).unwrap().command {
+            crate::cli::Commands::Stream(args) => Pipeline::from_args(&args).unwrap(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn transaction_update(signature: Vec<u8>) -> SubscribeUpdate {
+        SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature,
+                    meta: Some(TransactionStatusMeta::default()),
+                    ..Default::default()
+                }),
+                slot: 7,
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn skips_a_replayed_update_with_a_truncated_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("updates.rec");
+        let recorder = Recorder::create(&path).unwrap();
+        recorder.record(&transaction_update(vec![1; 10]));
+        recorder.close().await;
+
+        let mut pipeline = pipeline(&[]);
+        for update in RecordingReader::open(&path).unwrap() {
+            pipeline.process_update(update.unwrap()).await.unwrap();
+        }
+
+        assert_eq!(pipeline.stats.invalid_updates, 1);
+    }
+
+    // what a Kafka producer may publish: valid protobuf, not a usable transaction
+    #[tokio::test]
+    async fn skips_queue_messages_that_decode_but_are_malformed() {
+        use yellowstone_grpc_proto::prost::Message as _;
+
+        let no_transaction = SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                transaction: None,
+                slot: 7,
+            })),
+            ..Default::default()
+        };
+        let mut pipeline = pipeline(&[]);
+        for update in [no_transaction, transaction_update(vec![])] {
+            let payload = update.encode_to_vec();
+            let update = SubscribeUpdate::decode(payload.as_slice()).unwrap();
+            pipeline.process_update(update).await.unwrap();
+        }
+
+        assert_eq!(pipeline.stats.missing_meta, 1);
+        assert_eq!(pipeline.stats.invalid_updates, 1);
+    }
+
+    #[tokio::test]
+    async fn writes_launches_through_transform() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("launches.json");
+        let path = path.to_str().unwrap();
+        let sink = format!("file:{}", path);
+        let mut pipeline = pipeline(&["--transform", "cat", "--sink", &sink]);
+        let token_info = CreateTokenInfo {
+            mint: Pubkey::new_unique(),
+            ..Default::default()
+        };
+
+        pipeline.commit(&token_info);
+        pipeline.close().await;
+
+        let written = solana_pumpfun::utils::read_records(path, OutputFormat::Json).unwrap();
+        assert_eq!(written.len(), 1);
+        assert_eq!(written[0].mint, token_info.mint);
+    }
+
+    #[test]
+    fn measures_latency_from_the_provider_else_the_block_time() {
+        let detected_at = DateTime::from_timestamp_millis(1_718_900_002_250).unwrap();
+        let provider_created_at = DateTime::from_timestamp_millis(1_718_900_002_000);
+
+        assert_eq!(
+            detection_latency_ms(detected_at, provider_created_at, Some(1_718_900_000)),
+            Some(250)
+        );
+        assert_eq!(
+            detection_latency_ms(detected_at, None, Some(1_718_900_000)),
+            Some(2_250)
+        );
+        assert_eq!(detection_latency_ms(detected_at, None, None), None);
+    }
+}
@@ -0,0 +1,89 @@
+//! SOL/USD from an external feed, refreshed in the background so launches
+//! only ever read a cached value.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+// a price this many refresh intervals old is no longer reported
+const STALE_AFTER_REFRESHES: u32 = 5;
+
+#[derive(Clone)]
+pub struct SolPriceFeed {
+    latest: Arc<Mutex<Option<(f64, Instant)>>>,
+    max_age: Duration,
+}
+
+impl SolPriceFeed {
+    /// Starts refreshing from `url` every `refresh`. The response is either a
+    /// bare number or JSON, with `pointer` (e.g. `/solana/usd`) locating the
+    /// price inside it.
+    pub fn spawn(url: &str, pointer: Option<&str>, refresh: Duration) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build HTTP client: {}", e))?;
+        let feed = Self {
+            latest: Arc::new(Mutex::new(None)),
+            max_age: refresh * STALE_AFTER_REFRESHES,
+        };
+
+        let url = url.to_string();
+        let pointer = pointer.map(str::to_string);
+        let latest = feed.latest.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(refresh);
+            loop {
+                interval.tick().await;
+                match fetch_price(&client, &url, pointer.as_deref()).await {
+                    Ok(price) => *latest.lock().unwrap() = Some((price, Instant::now())),
+                    Err(e) => warn!("SOL price refresh failed: {:?}", e),
+                }
+            }
+        });
+
+        Ok(feed)
+    }
+
+    /// The last fetched price, unless it has gone stale.
+    pub fn latest(&self) -> Option<f64> {
+        self.latest
+            .lock()
+            .unwrap()
+            .filter(|(_, fetched_at)| fetched_at.elapsed() <= self.max_age)
+            .map(|(price, _)| price)
+    }
+}
+
+async fn fetch_price(
+    client: &reqwest::Client,
+    url: &str,
+    pointer: Option<&str>,
+) -> anyhow::Result<f64> {
+    let body = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let value: serde_json::Value = serde_json::from_str(body.trim())
+        .map_err(|e| anyhow::anyhow!("Invalid price response from {}: {}", url, e))?;
+    let value = match pointer {
+        Some(pointer) => value
+            .pointer(pointer)
+            .ok_or_else(|| anyhow::anyhow!("No {} in price response from {}", pointer, url))?,
+        None => &value,
+    };
+
+    // some feeds quote prices as strings
+    match value {
+        serde_json::Value::Number(price) => price.as_f64(),
+        serde_json::Value::String(price) => price.parse().ok(),
+        _ => None,
+    }
+    .filter(|price: &f64| price.is_finite() && *price > 0.0)
+    .ok_or_else(|| anyhow::anyhow!("Price from {} is not a positive number: {}", url, value))
+}
@@ -0,0 +1,69 @@
+use serde_json::{Map, Value};
+use solana_pumpfun::parser::CreateTokenInfo;
+
+use crate::display::PubkeyStyle;
+
+/// `--fields`: the subset of record fields consumers get, in the order given.
+#[derive(Clone, Debug)]
+pub struct FieldProjection {
+    fields: Vec<String>,
+}
+
+impl FieldProjection {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let known = match serde_json::to_value(CreateTokenInfo::default()) {
+            Ok(Value::Object(record)) => record,
+            _ => return Err("launch records don't serialize to an object".to_string()),
+        };
+
+        let fields: Vec<String> = value
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(str::to_string)
+            .collect();
+        if fields.is_empty() {
+            return Err("expected at least one field".to_string());
+        }
+        if let Some(unknown) = fields.iter().find(|field| !known.contains_key(*field)) {
+            let known: Vec<&str> = known.keys().map(String::as_str).collect();
+            return Err(format!(
+                "unknown field `{}`, expected one of: {}",
+                unknown,
+                known.join(", ")
+            ));
+        }
+
+        Ok(Self { fields })
+    }
+
+    pub fn project(&self, token_info: &CreateTokenInfo) -> Value {
+        let Ok(Value::Object(mut record)) = serde_json::to_value(token_info) else {
+            return Value::Null;
+        };
+
+        let mut projected = Map::new();
+        for field in &self.fields {
+            if let Some(value) = record.remove(field) {
+                projected.insert(field.clone(), value);
+            }
+        }
+        Value::Object(projected)
+    }
+
+    /// `field: value` lines for the console.
+    pub fn describe(&self, token_info: &CreateTokenInfo, pubkeys: PubkeyStyle) -> String {
+        let Value::Object(record) = self.project(token_info) else {
+            return String::new();
+        };
+
+        record
+            .iter()
+            .map(|(field, value)| match value {
+                Value::String(value) => format!("{}: {}", field, pubkeys.show_str(value)),
+                value => format!("{}: {}", field, value),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
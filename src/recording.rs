@@ -0,0 +1,162 @@
+//! `--record` files: every `SubscribeUpdate` as received, each a
+//! little-endian `u32` byte length followed by the protobuf-encoded message,
+//! the framing of the Borsh launch log.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use log::error;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use yellowstone_grpc_proto::prelude::SubscribeUpdate;
+use yellowstone_grpc_proto::prost::Message as _;
+
+// well over the gRPC message limit, a length past it is a corrupt recording
+const MAX_UPDATE_LEN: usize = 16 * 1024 * 1024;
+
+/// Appends updates to a recording from the blocking pool, flushing whenever
+/// it catches up, so the stream never waits on the disk.
+pub struct Recorder {
+    tx: mpsc::UnboundedSender<Vec<u8>>,
+    writer: JoinHandle<()>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> anyhow::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+        let path = path.to_path_buf();
+        let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+
+        let writer = tokio::task::spawn_blocking(move || {
+            let mut writer = BufWriter::new(file);
+            while let Some(frame) = rx.blocking_recv() {
+                let mut written = writer.write_all(&frame);
+                while written.is_ok()
+                    && let Ok(frame) = rx.try_recv()
+                {
+                    written = writer.write_all(&frame);
+                }
+                if let Err(e) = written.and_then(|()| writer.flush()) {
+                    error!("Recording to {} failed, stopped: {}", path.display(), e);
+                    return;
+                }
+            }
+        });
+        Ok(Self { tx, writer })
+    }
+
+    pub fn record(&self, update: &SubscribeUpdate) {
+        let bytes = update.encode_to_vec();
+        let mut frame = Vec::with_capacity(4 + bytes.len());
+        frame.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&bytes);
+        // only closed once the writer gave up, which it logged
+        let _ = self.tx.send(frame);
+    }
+
+    /// Writes out what's still queued.
+    pub async fn close(self) {
+        drop(self.tx);
+        let _ = self.writer.await;
+    }
+}
+
+/// Reads the updates of a recording until end of input.
+pub struct RecordingReader {
+    path: PathBuf,
+    reader: BufReader<File>,
+}
+
+impl RecordingReader {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            reader: BufReader::new(file),
+        })
+    }
+
+    fn read_update(&mut self) -> io::Result<Option<SubscribeUpdate>> {
+        let mut len = [0u8; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let len = u32::from_le_bytes(len) as usize;
+        if len > MAX_UPDATE_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "update of {} bytes, over the {} byte limit",
+                    len, MAX_UPDATE_LEN
+                ),
+            ));
+        }
+        let mut bytes = vec![0u8; len];
+        self.reader.read_exact(&mut bytes)?;
+        SubscribeUpdate::decode(bytes.as_slice())
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Iterator for RecordingReader {
+    type Item = anyhow::Result<SubscribeUpdate>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_update()
+            .map_err(|e| anyhow::anyhow!("Invalid recording {}: {}", self.path.display(), e))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yellowstone_grpc_proto::prelude::{SubscribeUpdatePing, subscribe_update::UpdateOneof};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn plays_back_what_was_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("updates.rec");
+        let update = SubscribeUpdate {
+            filters: vec!["pumpfun".to_string()],
+            update_oneof: Some(UpdateOneof::Ping(SubscribeUpdatePing {})),
+            ..Default::default()
+        };
+
+        let recorder = Recorder::create(&path).unwrap();
+        recorder.record(&update);
+        recorder.record(&update);
+        recorder.close().await;
+
+        let played: Vec<_> = RecordingReader::open(&path)
+            .unwrap()
+            .collect::<anyhow::Result<_>>()
+            .unwrap();
+        assert_eq!(played, [update.clone(), update]);
+    }
+
+    #[test]
+    fn rejects_an_oversized_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corrupt.rec");
+        std::fs::write(&path, u32::MAX.to_le_bytes()).unwrap();
+
+        let err = RecordingReader::open(&path)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert!(err.to_string().contains("over the"), "{}", err);
+    }
+}
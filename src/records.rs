@@ -0,0 +1,123 @@
+//! Length-prefixed Borsh framing for launch records: a header of `MAGIC`
+//! and a little-endian `u16` version, then each record as a little-endian
+//! `u32` byte length followed by the Borsh-encoded `CreateTokenInfo`.
+
+use std::io::{self, Read, Write};
+
+use borsh::BorshDeserialize;
+
+use crate::parser::CreateTokenInfo;
+
+pub const MAGIC: [u8; 4] = *b"PFLR";
+
+/// The `CreateTokenInfo` layout the records are in, bumped with every field
+/// added to it.
+pub const VERSION: u16 = 2;
+
+pub const HEADER_LEN: usize = MAGIC.len() + 2;
+
+// far over any real record, a length past it is a corrupt file
+pub const MAX_RECORD_LEN: usize = 16 * 1024 * 1024;
+
+/// Starts a log, before its first record.
+pub fn write_borsh_header(writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())
+}
+
+/// Checks a log's header is this version's, false for an empty log.
+pub fn read_borsh_header(reader: &mut impl Read) -> io::Result<bool> {
+    let mut header = [0u8; HEADER_LEN];
+    let mut filled = 0;
+    while filled < HEADER_LEN {
+        match reader.read(&mut header[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    if filled == 0 {
+        return Ok(false);
+    }
+    if filled < HEADER_LEN || header[..MAGIC.len()] != MAGIC {
+        return Err(invalid_data(
+            "no record log header, not a launch log or written before versioned logs".to_string(),
+        ));
+    }
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version != VERSION {
+        return Err(invalid_data(format!(
+            "record log version {}, this build reads version {}",
+            version, VERSION
+        )));
+    }
+    Ok(true)
+}
+
+pub fn write_borsh_record(writer: &mut impl Write, token_info: &CreateTokenInfo) -> io::Result<()> {
+    let bytes = borsh::to_vec(token_info)?;
+    if bytes.len() > MAX_RECORD_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "record too large",
+        ));
+    }
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)
+}
+
+/// Reads the header and then the records written by `write_borsh_record`
+/// until end of input.
+pub struct BorshRecordReader<R> {
+    reader: R,
+    header_read: bool,
+}
+
+impl<R: Read> BorshRecordReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            header_read: false,
+        }
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<CreateTokenInfo>> {
+        if !self.header_read {
+            if !read_borsh_header(&mut self.reader)? {
+                return Ok(None);
+            }
+            self.header_read = true;
+        }
+
+        let mut len = [0u8; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let len = u32::from_le_bytes(len) as usize;
+        if len > MAX_RECORD_LEN {
+            return Err(invalid_data(format!(
+                "record of {} bytes, over the {} byte limit",
+                len, MAX_RECORD_LEN
+            )));
+        }
+        let mut bytes = vec![0u8; len];
+        self.reader.read_exact(&mut bytes)?;
+        CreateTokenInfo::try_from_slice(&bytes).map(Some)
+    }
+}
+
+impl<R: Read> Iterator for BorshRecordReader<R> {
+    type Item = io::Result<CreateTokenInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
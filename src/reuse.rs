@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use log::warn;
+use solana_pumpfun::parser::CreateTokenInfo;
+
+/// Counts launches sharing the same metadata, the fetched document when
+/// `--fetch-metadata` got it and the uri otherwise. Template farms reuse one
+/// document across hundreds of launches with only the name changed.
+pub struct MetadataReuse {
+    // `--flag-metadata-reuse`
+    flag_at: Option<u32>,
+    counts: HashMap<u64, u32>,
+}
+
+impl MetadataReuse {
+    pub fn new(flag_at: Option<u32>) -> Self {
+        Self {
+            flag_at,
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Records `token_info`'s metadata and sets `metadata_reuse_count`, and
+    /// `suspected_farm` once the count reaches the threshold.
+    pub fn observe(&mut self, token_info: &mut CreateTokenInfo) {
+        let mut hasher = DefaultHasher::new();
+        match &token_info.metadata {
+            Some(metadata) => metadata.hash(&mut hasher),
+            None if token_info.uri.is_empty() => return,
+            None => token_info.uri.hash(&mut hasher),
+        }
+
+        let count = self.counts.entry(hasher.finish()).or_default();
+        *count += 1;
+        token_info.metadata_reuse_count = Some(*count);
+
+        if let Some(flag_at) = self.flag_at
+            && *count >= flag_at
+        {
+            token_info.suspected_farm = true;
+            warn!(
+                "{} ({}) shares its metadata with {} launches, suspected template farm",
+                token_info.mint, token_info.symbol, count
+            );
+        }
+    }
+}
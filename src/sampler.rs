@@ -0,0 +1,84 @@
+use log::info;
+use tokio::time::Instant;
+
+/// `--max-per-second` token bucket. Bursts up to the limit pass untouched,
+/// beyond it launches are dropped until the bucket refills.
+pub struct LaunchSampler {
+    max_per_second: u32,
+    tokens: f64,
+    refilled_at: Instant,
+    // dropped since `second_started_at`, logged once the second is over
+    dropped: u64,
+    second_started_at: Instant,
+}
+
+impl LaunchSampler {
+    pub fn new(max_per_second: u32) -> Self {
+        Self {
+            max_per_second,
+            tokens: max_per_second as f64,
+            refilled_at: Instant::now(),
+            dropped: 0,
+            second_started_at: Instant::now(),
+        }
+    }
+
+    /// Whether to keep the next launch.
+    pub fn admit(&mut self) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.max_per_second as f64).min(self.max_per_second as f64);
+        self.refilled_at = now;
+
+        let since_second = now.duration_since(self.second_started_at);
+        if since_second.as_secs() >= 1 {
+            if self.dropped > 0 {
+                info!(
+                    "--max-per-second dropped {} launches in the last {:.1}s",
+                    self.dropped,
+                    since_second.as_secs_f64()
+                );
+            }
+            self.dropped = 0;
+            self.second_started_at = now;
+        }
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return true;
+        }
+        self.dropped += 1;
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn passes_a_burst_up_to_the_limit() {
+        let mut sampler = LaunchSampler::new(3);
+
+        let admitted: Vec<_> = (0..5).map(|_| sampler.admit()).collect();
+
+        assert_eq!(admitted, [true, true, true, false, false]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refills_over_time() {
+        let mut sampler = LaunchSampler::new(4);
+        while sampler.admit() {}
+
+        tokio::time::advance(Duration::from_millis(500)).await;
+        let admitted: Vec<_> = (0..3).map(|_| sampler.admit()).collect();
+        assert_eq!(admitted, [true, true, false]);
+
+        // never more than a second's worth, however long it was idle
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!((0..5).filter(|_| sampler.admit()).count(), 4);
+    }
+}
@@ -0,0 +1,329 @@
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::Utc;
+use log::warn;
+use solana_pumpfun::parser::CreateTokenInfo;
+use solana_pumpfun::utils::{
+    OutputFormat, append_all_to_borsh_file, append_all_to_json_file_with, sync_file,
+};
+
+use super::LaunchHandler;
+use crate::cli::StreamArgs;
+use crate::projection::FieldProjection;
+
+// where `--partition-by` writes without an `--output`
+const DEFAULT_PARTITION_DIR: &str = "launches";
+
+// a full disk or a flaky network mount often clears within a few seconds
+const WRITE_ATTEMPTS: u32 = 4;
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// How `--partition-by` splits the log into files under the `--output` directory.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Partition {
+    /// one file, `--output` itself
+    #[default]
+    None,
+    /// `launches-YYYY-MM-DD` by the record's `created_at` (UTC)
+    Day,
+    /// one file per creator wallet
+    Creator,
+}
+
+/// The `--output` log. Writes run on the blocking pool, the JSON log is
+/// rewritten on every write and would otherwise park a runtime worker.
+/// A failed write is retried a few times per file, then that file's part of
+/// the batch is given up on and the stream keeps going.
+pub struct FileSink {
+    // the log, or the directory of partitions
+    path: String,
+    format: OutputFormat,
+    fields: Option<FieldProjection>,
+    partition: Partition,
+    quiet: bool,
+    // `--fsync` after every write
+    fsync: bool,
+    // `--pretty`, for the JSON log
+    pretty: bool,
+    first_retry_delay: Duration,
+}
+
+impl FileSink {
+    pub fn from_args(args: &StreamArgs) -> Self {
+        let path = match args.partition_by {
+            Partition::None => args.output_path(),
+            _ => args.output.as_deref().unwrap_or(DEFAULT_PARTITION_DIR),
+        };
+
+        Self {
+            path: path.to_string(),
+            format: args.format,
+            fields: args.fields.clone(),
+            partition: args.partition_by,
+            quiet: args.quiet,
+            fsync: args.fsync,
+            pretty: args.pretty,
+            first_retry_delay: FIRST_RETRY_DELAY,
+        }
+    }
+
+    /// A `--sink file:<path>`, one unpartitioned log.
+    pub fn extra(path: &str, args: &StreamArgs) -> Self {
+        let format = if path.ends_with(".borsh") {
+            OutputFormat::Borsh
+        } else {
+            OutputFormat::Json
+        };
+        Self {
+            path: path.to_string(),
+            format,
+            fields: args.fields.clone().filter(|_| format == OutputFormat::Json),
+            partition: Partition::None,
+            quiet: args.quiet,
+            fsync: args.fsync,
+            pretty: args.pretty,
+            first_retry_delay: FIRST_RETRY_DELAY,
+        }
+    }
+
+    fn partition_path(&self, token_info: &CreateTokenInfo) -> String {
+        let name = match self.partition {
+            Partition::None => return self.path.clone(),
+            Partition::Day => {
+                let created_at = token_info.created_at_utc().unwrap_or_else(Utc::now);
+                format!("launches-{}", created_at.format("%Y-%m-%d"))
+            }
+            Partition::Creator => token_info.user.to_string(),
+        };
+        Path::new(&self.path)
+            .join(format!("{}.{}", name, self.format.extension()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    // the batch by file, each in (slot, transaction index) order; stable, so
+    // launches of one transaction keep their arrival order
+    fn partitions(&self, batch: &[CreateTokenInfo]) -> Vec<(String, Vec<CreateTokenInfo>)> {
+        let mut batch: Vec<&CreateTokenInfo> = batch.iter().collect();
+        batch.sort_by_key(|token_info| (token_info.slot, token_info.tx_index));
+
+        let mut partitions: Vec<(String, Vec<CreateTokenInfo>)> = vec![];
+        for token_info in batch {
+            let path = self.partition_path(token_info);
+            match partitions
+                .iter_mut()
+                .find(|(partition, _)| *partition == path)
+            {
+                Some((_, records)) => records.push(token_info.clone()),
+                None => partitions.push((path, vec![token_info.clone()])),
+            }
+        }
+        partitions
+    }
+
+    async fn write(&self, path: &str, records: &[CreateTokenInfo]) -> anyhow::Result<()> {
+        let format = self.format;
+        let projected: Option<Vec<_>> = self.fields.as_ref().map(|fields| {
+            records
+                .iter()
+                .map(|token_info| fields.project(token_info))
+                .collect()
+        });
+        let records = records.to_vec();
+        let dir = (self.partition != Partition::None).then(|| self.path.clone());
+        let path = path.to_string();
+        let fsync = self.fsync;
+        let pretty = self.pretty;
+
+        tokio::task::spawn_blocking(move || {
+            if let Some(dir) = dir {
+                std::fs::create_dir_all(&dir)
+                    .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", dir, e))?;
+            }
+            match (projected, format) {
+                (Some(projected), _) => {
+                    append_all_to_json_file_with(&path, &projected, pretty, fsync)
+                }
+                (None, OutputFormat::Json) => {
+                    append_all_to_json_file_with(&path, &records, pretty, fsync)
+                }
+                // appended in place, synced once written
+                (None, OutputFormat::Borsh) => append_all_to_borsh_file(&path, &records)
+                    .and_then(|()| if fsync { sync_file(&path) } else { Ok(()) }),
+            }?;
+            Ok::<_, anyhow::Error>(())
+        })
+        .await?
+    }
+
+    // retried on its own, so a retry never appends a partition that already made it
+    async fn write_with_retry(
+        &self,
+        path: &str,
+        records: &[CreateTokenInfo],
+    ) -> anyhow::Result<()> {
+        let mut delay = self.first_retry_delay;
+        for _ in 1..WRITE_ATTEMPTS {
+            match self.write(path, records).await {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    warn!("Writing {} failed, retrying in {:?}: {:?}", path, delay, e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+            }
+        }
+        self.write(path, records).await
+    }
+}
+
+impl LaunchHandler for FileSink {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn handle(&mut self, token_info: &CreateTokenInfo) -> anyhow::Result<()> {
+        self.handle_batch(std::slice::from_ref(token_info)).await
+    }
+
+    // one rewrite of each file per batch rather than per launch. A partition
+    // that still fails is given up on, the others are written regardless
+    async fn handle_batch(&mut self, batch: &[CreateTokenInfo]) -> anyhow::Result<()> {
+        let mut failed = None;
+        for (path, records) in self.partitions(batch) {
+            match self.write_with_retry(&path, &records).await {
+                Ok(()) if !self.quiet => println!("Results logged to {}", path),
+                Ok(()) => {}
+                Err(e) => {
+                    failed.get_or_insert(e);
+                }
+            }
+        }
+        failed.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_pumpfun::utils::read_records;
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::*;
+
+    fn sink(dir: &Path, partition: Partition) -> FileSink {
+        FileSink {
+            path: dir.to_string_lossy().into_owned(),
+            format: OutputFormat::Json,
+            fields: None,
+            partition,
+            quiet: true,
+            fsync: false,
+            pretty: true,
+            first_retry_delay: Duration::from_millis(1),
+        }
+    }
+
+    fn launch(user: Pubkey, created_at: &str) -> CreateTokenInfo {
+        CreateTokenInfo {
+            mint: Pubkey::new_unique(),
+            user,
+            created_at: created_at.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn partitions_by_creator_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = sink(dir.path(), Partition::Creator);
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let batch = [
+            launch(a, "2024-06-01 10:00:00"),
+            launch(b, "2024-06-01 10:00:01"),
+            launch(a, "2024-06-01 10:00:02"),
+        ];
+
+        let partitions = sink.partitions(&batch);
+        assert_eq!(partitions.len(), 2);
+        assert!(partitions[0].0.ends_with(&format!("{}.json", a)));
+        assert_eq!(partitions[0].1, [batch[0].clone(), batch[2].clone()]);
+        assert!(partitions[1].0.ends_with(&format!("{}.json", b)));
+        assert_eq!(partitions[1].1, [batch[1].clone()]);
+    }
+
+    fn at(slot: u64, tx_index: u64) -> CreateTokenInfo {
+        CreateTokenInfo {
+            slot,
+            tx_index,
+            ..launch(Pubkey::default(), "2024-06-01 10:00:00")
+        }
+    }
+
+    #[test]
+    fn sorts_each_batch_by_slot_then_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = sink(dir.path(), Partition::None);
+        let batch = [at(11, 0), at(10, 7), at(10, 2), at(10, 7), at(9, 3)];
+
+        let partitions = sink.partitions(&batch);
+        assert_eq!(partitions.len(), 1);
+        // the two launches of transaction (10, 7) keep their arrival order
+        assert_eq!(
+            partitions[0].1,
+            [
+                batch[4].clone(),
+                batch[2].clone(),
+                batch[1].clone(),
+                batch[3].clone(),
+                batch[0].clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn partitions_by_created_at_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = sink(dir.path(), Partition::Day);
+        let user = Pubkey::new_unique();
+        let batch = [
+            launch(user, "2024-06-01 23:59:59"),
+            launch(user, "2024-06-02 00:00:00"),
+        ];
+
+        let paths: Vec<_> = sink
+            .partitions(&batch)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert!(
+            paths[0].ends_with("launches-2024-06-01.json"),
+            "{:?}",
+            paths
+        );
+        assert!(
+            paths[1].ends_with("launches-2024-06-02.json"),
+            "{:?}",
+            paths
+        );
+    }
+
+    // the second creator's file can't be written, the first's must not be
+    // appended again by the retries
+    #[tokio::test]
+    async fn retries_a_failed_partition_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = sink(dir.path(), Partition::Creator);
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let batch = [
+            launch(a, "2024-06-01 10:00:00"),
+            launch(b, "2024-06-01 10:00:01"),
+        ];
+        let partitions = sink.partitions(&batch);
+        std::fs::create_dir_all(&partitions[1].0).unwrap();
+
+        assert!(sink.handle_batch(&batch).await.is_err());
+        let written = read_records(&partitions[0].0, OutputFormat::Json).unwrap();
+        assert_eq!(written, [batch[0].clone()]);
+    }
+}
@@ -0,0 +1,412 @@
+pub mod file;
+#[cfg(feature = "nats")]
+pub mod nats;
+#[cfg(feature = "pubsub")]
+pub mod pubsub;
+pub mod redis;
+#[cfg(feature = "sns")]
+pub mod sns;
+pub mod stdout;
+pub mod webhook;
+
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use futures_util::FutureExt as _;
+use log::{error, warn};
+use solana_pumpfun::parser::CreateTokenInfo;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+use tracing::{Instrument, Span, info_span};
+
+use crate::control::ControlEvent;
+
+// every sink's `LaunchHandler::name`, for per-sink options
+pub const SINK_NAMES: &[&str] = &[
+    "file",
+    "redis",
+    #[cfg(feature = "nats")]
+    "nats",
+    #[cfg(feature = "pubsub")]
+    "pubsub",
+    #[cfg(feature = "sns")]
+    "sns",
+    "stdout",
+    "webhook",
+];
+
+/// An extra output from `--sink`, on top of the console and the `--output` log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SinkSpec {
+    /// `stdout-jsonl`
+    StdoutJsonl,
+    /// `file:<path>`, Borsh records for a `.borsh` path and the JSON log otherwise
+    File(String),
+    /// `webhook:<url>`
+    Webhook(String),
+}
+
+impl SinkSpec {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if value == "stdout-jsonl" {
+            return Ok(SinkSpec::StdoutJsonl);
+        }
+        match value.split_once(':') {
+            Some(("file", path)) if !path.is_empty() => Ok(SinkSpec::File(path.to_string())),
+            Some(("webhook", url)) if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(SinkSpec::Webhook(url.to_string()))
+            }
+            _ => Err(format!(
+                "expected stdout-jsonl, file:<path> or webhook:<http(s) url>, got {:?}",
+                value
+            )),
+        }
+    }
+}
+
+/// A destination for launch records. Each handler runs in its own task behind a
+/// bounded buffer, so a slow or unavailable sink never stalls the stream. An
+/// error or panic is logged and counted against the sink, which carries on
+/// with the next launch.
+pub trait LaunchHandler: Send + 'static {
+    fn name(&self) -> &'static str;
+
+    fn handle(
+        &mut self,
+        token_info: &CreateTokenInfo,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    /// Handles a batch collected under the sink's `BatchPolicy`, one launch at
+    /// a time unless the sink can do better.
+    fn handle_batch(
+        &mut self,
+        batch: &[CreateTokenInfo],
+    ) -> impl Future<Output = anyhow::Result<()>> + Send {
+        async move {
+            for token_info in batch {
+                self.handle(token_info).await?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Passes on a stream start, reconnect or stop, after every launch
+    /// before it. Ignored unless the sink feeds live consumers.
+    fn handle_control(
+        &mut self,
+        _event: &ControlEvent,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send {
+        async { Ok(()) }
+    }
+}
+
+// launches are nearly every message, boxing them would only add an allocation
+#[allow(clippy::large_enum_variant)]
+enum SinkMessage {
+    // the span is the launch's, so sink writes land in its trace
+    Launch(CreateTokenInfo, Span),
+    Control(ControlEvent),
+}
+
+/// A batch is handed to the sink once it holds `max_size` launches or its
+/// first launch has waited `max_delay`, whichever comes first.
+#[derive(Clone, Copy, Debug)]
+pub struct BatchPolicy {
+    pub max_size: usize,
+    pub max_delay: Duration,
+}
+
+pub struct SinkHandle {
+    name: &'static str,
+    tx: mpsc::Sender<SinkMessage>,
+    task: JoinHandle<()>,
+    // handler calls that errored or panicked
+    failures: Arc<AtomicU64>,
+}
+
+impl SinkHandle {
+    pub fn send(&self, token_info: &CreateTokenInfo) {
+        match self
+            .tx
+            .try_send(SinkMessage::Launch(token_info.clone(), Span::current()))
+        {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => warn!(
+                "{} sink buffer full, dropping launch {}",
+                self.name, token_info.mint
+            ),
+            Err(TrySendError::Closed(_)) => error!(
+                "{} sink stopped, dropping launch {}",
+                self.name, token_info.mint
+            ),
+        }
+    }
+
+    pub fn send_control(&self, event: &ControlEvent) {
+        if let Err(e) = self.tx.try_send(SinkMessage::Control(event.clone())) {
+            warn!("{} sink dropped {} event: {}", self.name, event.kind(), e);
+        }
+    }
+
+    /// Stops accepting launches and waits, bounded by `timeout`, for the
+    /// buffered ones to be handled. Returns the sink name and how many of its
+    /// writes failed over the session.
+    pub async fn close(self, timeout: Duration) -> (&'static str, u64) {
+        drop(self.tx);
+        match tokio::time::timeout(timeout, self.task).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("{} sink task failed: {:?}", self.name, e),
+            Err(_) => warn!("{} sink did not flush within {:?}", self.name, timeout),
+        }
+        (self.name, self.failures.load(Ordering::Relaxed))
+    }
+}
+
+pub fn spawn_sink<H: LaunchHandler>(
+    mut handler: H,
+    buffer: usize,
+    policy: BatchPolicy,
+) -> SinkHandle {
+    let name = handler.name();
+    let (tx, mut rx) = mpsc::channel::<SinkMessage>(buffer);
+    let max_size = policy.max_size.max(1);
+    let failures = Arc::new(AtomicU64::new(0));
+    let failed = {
+        let failures = failures.clone();
+        move || {
+            failures.fetch_add(1, Ordering::Relaxed);
+        }
+    };
+
+    let task = tokio::spawn(async move {
+        // a control event that closed the previous batch
+        let mut next = None;
+        loop {
+            let message = match next.take() {
+                Some(message) => message,
+                None => match rx.recv().await {
+                    Some(message) => message,
+                    None => break,
+                },
+            };
+            let (token_info, span) = match message {
+                SinkMessage::Launch(token_info, span) => (token_info, span),
+                SinkMessage::Control(event) => {
+                    if let Err(e) = isolated(handler.handle_control(&event)).await {
+                        error!("{} sink failed on {}: {:?}", name, event.kind(), e);
+                        failed();
+                    }
+                    continue;
+                }
+            };
+
+            if max_size == 1 {
+                let handled = isolated(handler.handle(&token_info))
+                    .instrument(info_span!(parent: &span, "sink", sink = name))
+                    .await;
+                if let Err(e) = handled {
+                    error!("{} sink failed on {}: {:?}", name, token_info.mint, e);
+                    failed();
+                }
+                continue;
+            }
+
+            let mut batch = vec![token_info];
+            let deadline = Instant::now() + policy.max_delay;
+            while batch.len() < max_size {
+                match tokio::time::timeout_at(deadline, rx.recv()).await {
+                    Ok(Some(SinkMessage::Launch(token_info, _))) => batch.push(token_info),
+                    // handled once the launches before it are
+                    Ok(Some(control)) => {
+                        next = Some(control);
+                        break;
+                    }
+                    Ok(None) | Err(_) => break,
+                }
+            }
+
+            // the batch lands in the trace of the launch that opened it
+            let handled = isolated(handler.handle_batch(&batch))
+                .instrument(info_span!(parent: &span, "sink", sink = name, batch = batch.len()))
+                .await;
+            if let Err(e) = handled {
+                error!(
+                    "{} sink failed on a batch of {}: {:?}",
+                    name,
+                    batch.len(),
+                    e
+                );
+                failed();
+            }
+        }
+    });
+
+    SinkHandle {
+        name,
+        tx,
+        task,
+        failures,
+    }
+}
+
+// a panicking handler becomes one more failed write rather than a dead sink
+async fn isolated(handled: impl Future<Output = anyhow::Result<()>>) -> anyhow::Result<()> {
+    match AssertUnwindSafe(handled).catch_unwind().await {
+        Ok(handled) => handled,
+        Err(panic) => Err(anyhow::anyhow!("panicked: {}", panic_message(&panic))),
+    }
+}
+
+fn panic_message(panic: &Box<dyn Any + Send>) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "(no message)"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use chrono::Utc;
+
+    use super::*;
+
+    // a batch of launch names or a control event, as the sink was handed them
+    #[derive(Debug, PartialEq)]
+    enum Handled {
+        Batch(Vec<String>),
+        Control(&'static str),
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder {
+        handled: Arc<Mutex<Vec<Handled>>>,
+    }
+
+    impl LaunchHandler for Recorder {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        async fn handle(&mut self, token_info: &CreateTokenInfo) -> anyhow::Result<()> {
+            if token_info.name == "panic" {
+                panic!("bad launch");
+            }
+            let name = token_info.name.clone();
+            self.handled
+                .lock()
+                .unwrap()
+                .push(Handled::Batch(vec![name]));
+            Ok(())
+        }
+
+        async fn handle_batch(&mut self, batch: &[CreateTokenInfo]) -> anyhow::Result<()> {
+            let names = batch.iter().map(|t| t.name.clone()).collect();
+            self.handled.lock().unwrap().push(Handled::Batch(names));
+            Ok(())
+        }
+
+        async fn handle_control(&mut self, event: &ControlEvent) -> anyhow::Result<()> {
+            self.handled
+                .lock()
+                .unwrap()
+                .push(Handled::Control(event.kind()));
+            Ok(())
+        }
+    }
+
+    fn launch(name: &str) -> CreateTokenInfo {
+        CreateTokenInfo {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn batch(names: &[&str]) -> Handled {
+        Handled::Batch(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    fn policy(max_size: usize) -> BatchPolicy {
+        BatchPolicy {
+            max_size,
+            max_delay: Duration::from_secs(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn splits_batches_at_the_max_size() {
+        let recorder = Recorder::default();
+        let sink = spawn_sink(recorder.clone(), 16, policy(2));
+
+        for name in ["a", "b", "c", "d", "e"] {
+            sink.send(&launch(name));
+        }
+        sink.close(Duration::from_secs(10)).await;
+
+        assert_eq!(
+            *recorder.handled.lock().unwrap(),
+            [batch(&["a", "b"]), batch(&["c", "d"]), batch(&["e"])]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flushes_a_partial_batch_after_the_max_delay() {
+        let recorder = Recorder::default();
+        let sink = spawn_sink(recorder.clone(), 16, policy(10));
+
+        sink.send(&launch("a"));
+        sink.send(&launch("b"));
+        tokio::time::sleep(Duration::from_secs(4)).await;
+        assert!(recorder.handled.lock().unwrap().is_empty());
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(*recorder.handled.lock().unwrap(), [batch(&["a", "b"])]);
+        sink.close(Duration::from_secs(10)).await;
+    }
+
+    #[tokio::test]
+    async fn closes_a_batch_at_a_control_event() {
+        let recorder = Recorder::default();
+        let sink = spawn_sink(recorder.clone(), 16, policy(10));
+
+        sink.send(&launch("a"));
+        sink.send_control(&ControlEvent::StreamStopped {
+            at: Utc::now(),
+            launches: 1,
+        });
+        sink.send(&launch("b"));
+        sink.close(Duration::from_secs(10)).await;
+
+        assert_eq!(
+            *recorder.handled.lock().unwrap(),
+            [
+                batch(&["a"]),
+                Handled::Control("stream_stopped"),
+                batch(&["b"])
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn counts_a_panic_and_carries_on() {
+        let recorder = Recorder::default();
+        let sink = spawn_sink(recorder.clone(), 16, policy(1));
+
+        sink.send(&launch("panic"));
+        sink.send(&launch("a"));
+        let (_, failures) = sink.close(Duration::from_secs(10)).await;
+
+        assert_eq!(failures, 1);
+        assert_eq!(*recorder.handled.lock().unwrap(), [batch(&["a"])]);
+    }
+}
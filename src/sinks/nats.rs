@@ -0,0 +1,93 @@
+use async_nats::{Client, ConnectOptions, HeaderMap};
+use log::info;
+use solana_pumpfun::parser::CreateTokenInfo;
+
+use super::LaunchHandler;
+use crate::control::ControlEvent;
+use crate::projection::FieldProjection;
+
+/// Publishes each launch as JSON to `<subject>.<creator>`, so subscribers can
+/// take one creator or `<subject>.>` for all, with the mint as a `mint` header.
+/// Control events go to `<subject>.control`. The client reconnects on its own
+/// and buffers publishes while disconnected.
+pub struct NatsSink {
+    url: String,
+    subject: String,
+    // connected on first publish, in the background if the server is down
+    client: Option<Client>,
+    fields: Option<FieldProjection>,
+}
+
+impl NatsSink {
+    pub fn new(url: &str, subject: &str, fields: Option<FieldProjection>) -> anyhow::Result<Self> {
+        let subject = subject.trim_end_matches('.');
+        if subject.is_empty() || subject.contains([' ', '*', '>']) {
+            anyhow::bail!(
+                "NATS subject {:?} should be a plain prefix like pumpfun.launch",
+                subject
+            );
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            subject: subject.to_string(),
+            client: None,
+            fields,
+        })
+    }
+
+    async fn client(&mut self) -> anyhow::Result<Client> {
+        if let Some(client) = &self.client {
+            return Ok(client.clone());
+        }
+        let client = ConnectOptions::new()
+            .retry_on_initial_connect()
+            .connect(self.url.as_str())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to NATS at {}: {}", self.url, e))?;
+        info!(
+            "Connected to NATS, publishing to {}.<creator>",
+            self.subject
+        );
+        self.client = Some(client.clone());
+        Ok(client)
+    }
+}
+
+impl LaunchHandler for NatsSink {
+    fn name(&self) -> &'static str {
+        "nats"
+    }
+
+    async fn handle(&mut self, token_info: &CreateTokenInfo) -> anyhow::Result<()> {
+        let payload = match &self.fields {
+            Some(fields) => fields.project(token_info).to_string(),
+            None => serde_json::to_string(token_info)?,
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("mint", token_info.mint.to_string().as_str());
+
+        self.client()
+            .await?
+            .publish_with_headers(
+                format!("{}.{}", self.subject, token_info.user),
+                headers,
+                payload.into(),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("NATS publish failed: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn handle_control(&mut self, event: &ControlEvent) -> anyhow::Result<()> {
+        let payload = serde_json::to_string(event)?;
+        self.client()
+            .await?
+            .publish(format!("{}.control", self.subject), payload.into())
+            .await
+            .map_err(|e| anyhow::anyhow!("NATS publish failed: {}", e))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as base64;
+use gcp_auth::TokenProvider;
+use serde_json::json;
+use solana_pumpfun::parser::CreateTokenInfo;
+
+use super::LaunchHandler;
+use crate::control::ControlEvent;
+use crate::projection::FieldProjection;
+
+const PUBSUB_SCOPE: &str = "https://www.googleapis.com/auth/pubsub";
+
+/// Publishes each launch as a JSON message to a Google Cloud Pub/Sub topic,
+/// with the mint as a `mint` attribute for subscription filters. Control
+/// events carry a `control` attribute, their type, instead.
+/// Credentials come from Application Default Credentials.
+pub struct PubSubSink {
+    http: reqwest::Client,
+    publish_url: String,
+    // resolved on first publish, the metadata server may be slow to answer
+    auth: Option<Arc<dyn TokenProvider>>,
+    fields: Option<FieldProjection>,
+}
+
+impl PubSubSink {
+    /// `topic` is the full name, `projects/<project>/topics/<topic>`.
+    pub fn new(topic: &str, fields: Option<FieldProjection>) -> anyhow::Result<Self> {
+        if !topic.starts_with("projects/") || !topic.contains("/topics/") {
+            anyhow::bail!(
+                "Pub/Sub topic {:?} should look like projects/<project>/topics/<topic>",
+                topic
+            );
+        }
+
+        Ok(Self {
+            http: reqwest::Client::new(),
+            publish_url: format!("https://pubsub.googleapis.com/v1/{}:publish", topic),
+            auth: None,
+            fields,
+        })
+    }
+
+    async fn token(&mut self) -> anyhow::Result<String> {
+        let auth = match &self.auth {
+            Some(auth) => auth,
+            None => self.auth.insert(
+                gcp_auth::provider()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("No Google Cloud credentials: {}", e))?,
+            ),
+        };
+        let token = auth.token(&[PUBSUB_SCOPE]).await?;
+        Ok(token.as_str().to_string())
+    }
+
+    async fn publish(&mut self, messages: Vec<serde_json::Value>) -> anyhow::Result<()> {
+        let token = self.token().await?;
+        self.http
+            .post(&self.publish_url)
+            .bearer_auth(token)
+            .json(&json!({ "messages": messages }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+impl LaunchHandler for PubSubSink {
+    fn name(&self) -> &'static str {
+        "pubsub"
+    }
+
+    async fn handle(&mut self, token_info: &CreateTokenInfo) -> anyhow::Result<()> {
+        self.handle_batch(std::slice::from_ref(token_info)).await
+    }
+
+    // a publish request takes up to 1000 messages, so a batch is one request
+    async fn handle_batch(&mut self, batch: &[CreateTokenInfo]) -> anyhow::Result<()> {
+        let mut messages = Vec::with_capacity(batch.len());
+        for token_info in batch {
+            let payload = match &self.fields {
+                Some(fields) => fields.project(token_info).to_string(),
+                None => serde_json::to_string(token_info)?,
+            };
+            messages.push(json!({
+                "data": base64.encode(payload),
+                "attributes": { "mint": token_info.mint.to_string() },
+            }));
+        }
+
+        self.publish(messages).await
+    }
+
+    async fn handle_control(&mut self, event: &ControlEvent) -> anyhow::Result<()> {
+        let message = json!({
+            "data": base64.encode(serde_json::to_string(event)?),
+            "attributes": { "control": event.kind() },
+        });
+        self.publish(vec![message]).await
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use log::{info, warn};
+use redis::AsyncCommands;
+use redis::aio::MultiplexedConnection;
+use solana_pumpfun::parser::CreateTokenInfo;
+
+use super::LaunchHandler;
+use crate::control::ControlEvent;
+use crate::projection::FieldProjection;
+
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Publishes each launch as JSON to a Redis pub/sub channel, along with the
+/// stream's control events.
+pub struct RedisSink {
+    client: redis::Client,
+    channel: String,
+    connection: Option<MultiplexedConnection>,
+    fields: Option<FieldProjection>,
+}
+
+impl RedisSink {
+    pub fn new(url: &str, channel: &str, fields: Option<FieldProjection>) -> anyhow::Result<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| anyhow::anyhow!("Invalid Redis URL {}: {}", url, e))?;
+
+        Ok(Self {
+            client,
+            channel: channel.to_string(),
+            connection: None,
+            fields,
+        })
+    }
+
+    async fn publish(&mut self, payload: &str) -> redis::RedisResult<()> {
+        let connection = match &mut self.connection {
+            Some(connection) => connection,
+            None => {
+                let connection = self.client.get_multiplexed_async_connection().await?;
+                info!("Connected to Redis, publishing to {}", self.channel);
+                self.connection.insert(connection)
+            }
+        };
+
+        connection.publish::<_, _, ()>(&self.channel, payload).await
+    }
+
+    // retries until published; launches arriving meanwhile wait in the sink buffer
+    async fn publish_retrying(&mut self, payload: &str) {
+        let mut delay = Duration::from_millis(100);
+
+        loop {
+            match self.publish(payload).await {
+                Ok(()) => return,
+                Err(e) => {
+                    warn!("Redis publish failed, retrying in {:?}: {}", delay, e);
+                    self.connection = None;
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                }
+            }
+        }
+    }
+}
+
+impl LaunchHandler for RedisSink {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn handle(&mut self, token_info: &CreateTokenInfo) -> anyhow::Result<()> {
+        let payload = match &self.fields {
+            Some(fields) => fields.project(token_info).to_string(),
+            None => serde_json::to_string(token_info)?,
+        };
+        self.publish_retrying(&payload).await;
+        Ok(())
+    }
+
+    async fn handle_control(&mut self, event: &ControlEvent) -> anyhow::Result<()> {
+        self.publish_retrying(&serde_json::to_string(event)?).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use aws_sdk_sns::Client;
+use aws_sdk_sns::types::MessageAttributeValue;
+use solana_pumpfun::parser::CreateTokenInfo;
+
+use super::LaunchHandler;
+use crate::control::ControlEvent;
+use crate::projection::FieldProjection;
+
+/// Publishes each launch as a JSON message to an AWS SNS topic, with the mint
+/// as a `mint` message attribute for subscription filter policies. Control
+/// events carry a `control` attribute, their type, instead.
+/// Credentials and region follow the AWS SDK's default chain.
+pub struct SnsSink {
+    topic_arn: String,
+    // built on first publish, loading the config can mean a round trip to IMDS
+    client: Option<Client>,
+    fields: Option<FieldProjection>,
+}
+
+impl SnsSink {
+    pub fn new(topic_arn: &str, fields: Option<FieldProjection>) -> Self {
+        Self {
+            topic_arn: topic_arn.to_string(),
+            client: None,
+            fields,
+        }
+    }
+
+    async fn client(&mut self) -> Client {
+        if let Some(client) = &self.client {
+            return client.clone();
+        }
+        let client = Client::new(&aws_config::load_from_env().await);
+        self.client = Some(client.clone());
+        client
+    }
+
+    async fn publish(&mut self, payload: String, attribute: (&str, String)) -> anyhow::Result<()> {
+        let (name, value) = attribute;
+        let value = MessageAttributeValue::builder()
+            .data_type("String")
+            .string_value(value)
+            .build()?;
+
+        self.client()
+            .await
+            .publish()
+            .topic_arn(&self.topic_arn)
+            .message(payload)
+            .message_attributes(name, value)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("SNS publish failed: {}", e))?;
+
+        Ok(())
+    }
+}
+
+impl LaunchHandler for SnsSink {
+    fn name(&self) -> &'static str {
+        "sns"
+    }
+
+    async fn handle(&mut self, token_info: &CreateTokenInfo) -> anyhow::Result<()> {
+        let payload = match &self.fields {
+            Some(fields) => fields.project(token_info).to_string(),
+            None => serde_json::to_string(token_info)?,
+        };
+        self.publish(payload, ("mint", token_info.mint.to_string()))
+            .await
+    }
+
+    async fn handle_control(&mut self, event: &ControlEvent) -> anyhow::Result<()> {
+        let payload = serde_json::to_string(event)?;
+        self.publish(payload, ("control", event.kind().to_string()))
+            .await
+    }
+}
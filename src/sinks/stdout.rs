@@ -0,0 +1,46 @@
+use std::io::Write;
+
+use solana_pumpfun::parser::CreateTokenInfo;
+
+use super::LaunchHandler;
+use crate::projection::FieldProjection;
+
+/// `--sink stdout-jsonl`: one JSON record per line on stdout, for piping into
+/// another tool. Runs alongside the console printout unless `--quiet`.
+pub struct StdoutSink {
+    fields: Option<FieldProjection>,
+}
+
+impl StdoutSink {
+    pub fn new(fields: Option<FieldProjection>) -> Self {
+        Self { fields }
+    }
+}
+
+impl LaunchHandler for StdoutSink {
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
+    async fn handle(&mut self, token_info: &CreateTokenInfo) -> anyhow::Result<()> {
+        self.handle_batch(std::slice::from_ref(token_info)).await
+    }
+
+    async fn handle_batch(&mut self, batch: &[CreateTokenInfo]) -> anyhow::Result<()> {
+        let mut lines = String::new();
+        for token_info in batch {
+            let line = match &self.fields {
+                Some(fields) => fields.project(token_info).to_string(),
+                None => serde_json::to_string(token_info)?,
+            };
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+
+        // one write, so a batch's lines never interleave with the console's
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(lines.as_bytes())?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use serde_json::Value;
+use solana_pumpfun::parser::CreateTokenInfo;
+
+use super::LaunchHandler;
+use crate::control::ControlEvent;
+use crate::projection::FieldProjection;
+
+/// `--sink webhook:<url>`: POSTs each launch as JSON, a batch as a JSON array.
+/// Control events are POSTed too, told apart by their `type` field.
+pub struct WebhookSink {
+    http: reqwest::Client,
+    url: String,
+    fields: Option<FieldProjection>,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str, fields: Option<FieldProjection>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.to_string(),
+            fields,
+        }
+    }
+
+    fn record(&self, token_info: &CreateTokenInfo) -> anyhow::Result<Value> {
+        Ok(match &self.fields {
+            Some(fields) => fields.project(token_info),
+            None => serde_json::to_value(token_info)?,
+        })
+    }
+
+    async fn post(&self, body: &impl serde::Serialize) -> anyhow::Result<()> {
+        self.http
+            .post(&self.url)
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+impl LaunchHandler for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn handle(&mut self, token_info: &CreateTokenInfo) -> anyhow::Result<()> {
+        self.post(&self.record(token_info)?).await
+    }
+
+    async fn handle_batch(&mut self, batch: &[CreateTokenInfo]) -> anyhow::Result<()> {
+        if let [token_info] = batch {
+            return self.handle(token_info).await;
+        }
+        let records = batch
+            .iter()
+            .map(|token_info| self.record(token_info))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.post(&records).await
+    }
+
+    async fn handle_control(&mut self, event: &ControlEvent) -> anyhow::Result<()> {
+        self.post(event).await
+    }
+}
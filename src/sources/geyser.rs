@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::stream::StreamExt;
+use log::{error, info, warn};
+use tokio::sync::mpsc;
+use yellowstone_grpc_client::{GeyserGrpcClient, Interceptor};
+use yellowstone_grpc_proto::prelude::subscribe_update::UpdateOneof;
+use yellowstone_grpc_proto::prelude::{SubscribeRequest, SubscribeUpdate};
+
+use solana_pumpfun::stream;
+
+use super::TransactionSource;
+use crate::health::HealthState;
+
+const FIRST_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// A Yellowstone gRPC subscription, resubscribed with backoff whenever it
+/// fails or ends.
+pub struct GeyserSource<F> {
+    client: GeyserGrpcClient<F>,
+    request: SubscribeRequest,
+    // consecutive failed reconnects before giving up, None retries forever
+    max_reconnects: Option<u32>,
+}
+
+impl<F> GeyserSource<F> {
+    pub fn new(
+        client: GeyserGrpcClient<F>,
+        request: SubscribeRequest,
+        max_reconnects: Option<u32>,
+    ) -> Self {
+        Self {
+            client,
+            request,
+            max_reconnects,
+        }
+    }
+}
+
+impl<F: Interceptor + Send + Sync + 'static> TransactionSource for GeyserSource<F> {
+    fn name(&self) -> &'static str {
+        "geyser"
+    }
+
+    async fn run(
+        mut self,
+        tx: mpsc::Sender<SubscribeUpdate>,
+        health: Arc<HealthState>,
+    ) -> anyhow::Result<()> {
+        // the first subscription failing is a config problem, not a blip
+        let mut subscribe_stream =
+            Box::pin(stream::subscribe(&mut self.client, self.request.clone()).await?);
+        let mut failed_reconnects = 0;
+        let mut delay = FIRST_RECONNECT_DELAY;
+        let mut last_slot = None;
+
+        loop {
+            health.set_connected(true);
+            while let Some(message) = subscribe_stream.next().await {
+                match message {
+                    Ok(update) => {
+                        // delivering anything means the reconnect took
+                        failed_reconnects = 0;
+                        delay = FIRST_RECONNECT_DELAY;
+                        if let Some(UpdateOneof::Transaction(transaction)) = &update.update_oneof {
+                            last_slot = Some(transaction.slot);
+                        }
+                        if tx.send(update).await.is_err() {
+                            health.set_connected(false);
+                            return Ok(());
+                        }
+                    }
+                    Err(e) => {
+                        error!("Stream error: {:?}", e);
+                        break;
+                    }
+                }
+            }
+            health.set_connected(false);
+            if tx.is_closed() {
+                return Ok(());
+            }
+
+            // a replay picks up where the stream dropped rather than where it began
+            let mut request = self.request.clone();
+            if request.from_slot.is_some() {
+                request.from_slot = last_slot.or(request.from_slot);
+            }
+            loop {
+                if let Some(max_reconnects) = self.max_reconnects
+                    && failed_reconnects >= max_reconnects
+                {
+                    anyhow::bail!(
+                        "Giving up on the Geyser stream after {} consecutive failed reconnects",
+                        failed_reconnects
+                    );
+                }
+                warn!(
+                    "Geyser stream down, reconnecting in {:?} (attempt {})",
+                    delay,
+                    failed_reconnects + 1
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                health.record_reconnect();
+                failed_reconnects += 1;
+
+                match stream::subscribe(&mut self.client, request.clone()).await {
+                    Ok(resubscribed) => {
+                        info!("Resubscribed to Geyser");
+                        subscribe_stream = Box::pin(resubscribed);
+                        break;
+                    }
+                    Err(e) => error!("Reconnect failed: {:?}", e),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use log::{error, info, warn};
+use rdkafka::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::Message;
+use tokio::sync::mpsc;
+use yellowstone_grpc_proto::prelude::SubscribeUpdate;
+use yellowstone_grpc_proto::prost::Message as _;
+
+use super::TransactionSource;
+use crate::cli::KafkaArgs;
+use crate::health::HealthState;
+
+/// A Kafka topic of protobuf-encoded `SubscribeUpdate`s, as fanned out from
+/// Geyser by an upstream producer.
+pub struct KafkaSource {
+    consumer: StreamConsumer,
+    topic: String,
+}
+
+impl KafkaSource {
+    pub fn new(args: &KafkaArgs) -> anyhow::Result<Self> {
+        // clap requires both with `--source kafka`
+        let brokers = args.kafka_brokers.as_deref().unwrap_or_default();
+        let topic = args.kafka_topic.as_deref().unwrap_or_default();
+
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("group.id", &args.kafka_group_id)
+            .set("enable.auto.commit", "true")
+            // a live consumer, starting a new group from history would replay old launches
+            .set("auto.offset.reset", "latest")
+            .create()
+            .map_err(|e| anyhow::anyhow!("Invalid Kafka config for {}: {}", brokers, e))?;
+        consumer
+            .subscribe(&[topic])
+            .map_err(|e| anyhow::anyhow!("Failed to subscribe to {}: {}", topic, e))?;
+
+        Ok(Self {
+            consumer,
+            topic: topic.to_string(),
+        })
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+}
+
+impl TransactionSource for KafkaSource {
+    fn name(&self) -> &'static str {
+        "kafka"
+    }
+
+    async fn run(
+        self,
+        tx: mpsc::Sender<SubscribeUpdate>,
+        health: Arc<HealthState>,
+    ) -> anyhow::Result<()> {
+        let mut connected = false;
+        loop {
+            let message = match self.consumer.recv().await {
+                Ok(message) => message,
+                Err(e) => {
+                    // librdkafka reconnects on its own, this only surfaces the errors
+                    error!("Kafka error on {}: {}", self.topic, e);
+                    connected = false;
+                    health.set_connected(false);
+                    continue;
+                }
+            };
+            if !connected {
+                info!("Consuming {}", self.topic);
+                connected = true;
+                health.set_connected(true);
+            }
+
+            let Some(payload) = message.payload() else {
+                continue;
+            };
+            // a message that decodes but isn't a usable transaction is skipped
+            // by the pipeline, the offset is committed either way
+            match SubscribeUpdate::decode(payload) {
+                Ok(update) => {
+                    if tx.send(update).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Err(e) => warn!(
+                    "Skipping undecodable message at {}/{}@{}: {}",
+                    self.topic,
+                    message.partition(),
+                    message.offset(),
+                    e
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+pub mod geyser;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod recording;
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use yellowstone_grpc_proto::prelude::SubscribeUpdate;
+
+use crate::health::HealthState;
+
+/// Where updates come from. Each source runs in its own task feeding the
+/// pipeline's channel, so a gRPC subscription and a Kafka topic are
+/// interchangeable.
+pub trait TransactionSource: Send + 'static {
+    fn name(&self) -> &'static str;
+
+    /// Whether the source ends on its own once done, rather than only on failure.
+    fn is_finite(&self) -> bool {
+        false
+    }
+
+    /// Forwards updates into `tx` until the source ends or `tx` is closed.
+    fn run(
+        self,
+        tx: mpsc::Sender<SubscribeUpdate>,
+        health: Arc<HealthState>,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+/// Runs `source` in its own task, which ends with the source's error, if any.
+/// The stream loop returns it once the channel closes.
+pub fn spawn_source<S: TransactionSource>(
+    source: S,
+    tx: mpsc::Sender<SubscribeUpdate>,
+    health: Arc<HealthState>,
+) -> JoinHandle<anyhow::Result<()>> {
+    let name = source.name();
+    tokio::spawn(async move {
+        source
+            .run(tx, health)
+            .await
+            .map_err(|e| e.context(format!("{} source failed", name)))
+    })
+}
@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use yellowstone_grpc_proto::prelude::SubscribeUpdate;
+
+use super::TransactionSource;
+use crate::health::HealthState;
+use crate::recording::RecordingReader;
+
+/// A `--record` file played back in order, as fast as the pipeline takes it.
+pub struct RecordingSource {
+    path: PathBuf,
+}
+
+impl RecordingSource {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl TransactionSource for RecordingSource {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+
+    fn is_finite(&self) -> bool {
+        true
+    }
+
+    async fn run(
+        self,
+        tx: mpsc::Sender<SubscribeUpdate>,
+        health: Arc<HealthState>,
+    ) -> anyhow::Result<()> {
+        let reader = RecordingReader::open(&self.path)?;
+        health.set_connected(true);
+        tokio::task::spawn_blocking(move || {
+            for update in reader {
+                if tx.blocking_send(update?).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        })
+        .await?
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::Instant;
+
+use solana_pumpfun::parser::CreateTokenInfo;
+use solana_sdk::pubkey::Pubkey;
+
+// upper bounds (ms) of the latency histogram buckets, last bucket is +Inf
+const LATENCY_BUCKETS_MS: [i64; 10] = [50, 100, 200, 300, 500, 750, 1_000, 2_000, 5_000, 10_000];
+// number of recent samples kept for the rolling percentiles
+const LATENCY_WINDOW: usize = 1_000;
+
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    count: u64,
+    sum_ms: i64,
+    recent: VecDeque<i64>,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; LATENCY_BUCKETS_MS.len() + 1],
+            count: 0,
+            sum_ms: 0,
+            recent: VecDeque::with_capacity(LATENCY_WINDOW),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn observe(&mut self, latency_ms: i64) {
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| latency_ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum_ms += latency_ms;
+
+        if self.recent.len() == LATENCY_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(latency_ms);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Percentile (0.0..=1.0) over the rolling window of recent samples.
+    pub fn percentile(&self, p: f64) -> Option<i64> {
+        if self.recent.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<i64> = self.recent.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+        Some(sorted[rank])
+    }
+
+    pub fn mean(&self) -> Option<i64> {
+        (self.count > 0).then(|| self.sum_ms / self.count as i64)
+    }
+
+    /// Per-bucket `(label, count)` pairs for display.
+    pub fn buckets(&self) -> Vec<(String, u64)> {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(i, count)| match LATENCY_BUCKETS_MS.get(i) {
+                Some(bound) => (format!("<={}ms", bound), *count),
+                None => (format!(">{}ms", LATENCY_BUCKETS_MS[i - 1]), *count),
+            })
+            .collect()
+    }
+}
+
+// keys a tally holds before the least frequent half is dropped, bounding a
+// long session's creators and symbols
+const MAX_TALLY_KEYS: usize = 100_000;
+
+/// Occurrence counts per key. Past `max_keys` the least frequent keys are
+/// dropped, keeping at most half, so a dropped key's count starts over; the
+/// top rows stay exact as long as they're well clear of the rest.
+#[derive(Debug)]
+pub struct Tally<K> {
+    counts: HashMap<K, u64>,
+    max_keys: usize,
+}
+
+impl<K> Default for Tally<K> {
+    fn default() -> Self {
+        Self {
+            counts: HashMap::new(),
+            max_keys: MAX_TALLY_KEYS,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Ord> Tally<K> {
+    pub fn observe(&mut self, key: K) {
+        *self.counts.entry(key).or_default() += 1;
+        if self.counts.len() > self.max_keys {
+            self.prune();
+        }
+    }
+
+    /// The `n` most frequent keys, ties broken by key so the order is stable.
+    pub fn top(&self, n: usize) -> Vec<(&K, u64)> {
+        let mut top: Vec<(&K, u64)> = self.counts.iter().map(|(k, count)| (k, *count)).collect();
+        top.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        top.truncate(n);
+        top
+    }
+
+    fn prune(&mut self) {
+        let mut counts: Vec<u64> = self.counts.values().copied().collect();
+        let keep = self.max_keys / 2;
+        // the count of the `keep`th most frequent key, ties past it go too
+        let (_, floor, _) = counts.select_nth_unstable_by(keep, |a, b| b.cmp(a));
+        let floor = *floor;
+        self.counts.retain(|_, count| *count > floor);
+    }
+}
+
+/// Launch counts by creator and by symbol.
+#[derive(Debug, Default)]
+pub struct LaunchTallies {
+    pub launches: u64,
+    // dropped by `--max-per-second`, not counted in `launches`
+    pub sampled_out: u64,
+    pub creators: Tally<Pubkey>,
+    pub symbols: Tally<String>,
+    // `--tally-instructions`: every Pumpfun instruction by name, not just launches
+    pub instructions: Tally<String>,
+}
+
+impl LaunchTallies {
+    pub fn observe(&mut self, token_info: &CreateTokenInfo) {
+        self.launches += 1;
+        self.creators.observe(token_info.user);
+        self.symbols.observe(token_info.symbol.clone());
+    }
+
+    // all of them, a name the parser doesn't know is the point
+    fn print_instructions(&self) {
+        let instructions = self.instructions.top(usize::MAX);
+        if instructions.is_empty() {
+            return;
+        }
+        let instructions: Vec<String> = instructions
+            .into_iter()
+            .map(|(name, count)| format!("{} {}", name, count))
+            .collect();
+        println!("Instructions: {}", instructions.join(", "));
+    }
+
+    fn print_top(&self, n: usize) {
+        if n == 0 || self.launches == 0 {
+            return;
+        }
+        println!("Top Creators:");
+        for (creator, count) in self.creators.top(n) {
+            println!("  {:>5}  {}", count, creator);
+        }
+        println!("Top Symbols:");
+        for (symbol, count) in self.symbols.top(n) {
+            println!("  {:>5}  {}", count, symbol);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SessionStats {
+    pub started_at: Instant,
+    pub detection_latency: LatencyHistogram,
+    // transactions skipped because the provider sent no meta
+    pub missing_meta: u64,
+    // transactions skipped as malformed, e.g. a truncated signature
+    pub invalid_updates: u64,
+    // launches `--dedup-window` dropped as redelivered
+    pub duplicates: u64,
+    // launches `--require-first-trade` / `--min-launches-to-write` never released
+    pub gated_out: u64,
+    // rows shown in the top creators / symbols tables
+    pub top_n: usize,
+    pub session: LaunchTallies,
+    // since the last `--summary-interval` printout
+    pub window: LaunchTallies,
+    window_started_at: Instant,
+}
+
+impl SessionStats {
+    pub fn new(top_n: usize) -> Self {
+        Self {
+            started_at: Instant::now(),
+            detection_latency: LatencyHistogram::default(),
+            missing_meta: 0,
+            invalid_updates: 0,
+            duplicates: 0,
+            gated_out: 0,
+            top_n,
+            session: LaunchTallies::default(),
+            window: LaunchTallies::default(),
+            window_started_at: Instant::now(),
+        }
+    }
+
+    pub fn record_launch(&mut self, token_info: &CreateTokenInfo) {
+        self.session.observe(token_info);
+        self.window.observe(token_info);
+    }
+
+    pub fn record_instruction(&mut self, name: &str) {
+        self.session.instructions.observe(name.to_string());
+        self.window.instructions.observe(name.to_string());
+    }
+
+    pub fn record_sampled_out(&mut self) {
+        self.session.sampled_out += 1;
+        self.window.sampled_out += 1;
+    }
+
+    /// Prints the launches since the last call and starts a new window.
+    pub fn print_window(&mut self) {
+        println!(
+            "Summary (last {}s): {} launches",
+            self.window_started_at.elapsed().as_secs(),
+            self.window.launches
+        );
+        if self.window.sampled_out > 0 {
+            println!("Dropped By Sampling: {}", self.window.sampled_out);
+        }
+        self.window.print_instructions();
+        self.window.print_top(self.top_n);
+        println!("---");
+        self.window = LaunchTallies::default();
+        self.window_started_at = Instant::now();
+    }
+
+    pub fn print_summary(&self) {
+        println!(
+            "Session Summary:\n\
+        Uptime: {}s\n\
+        Launches: {}",
+            self.started_at.elapsed().as_secs(),
+            self.session.launches
+        );
+
+        let latency = &self.detection_latency;
+        match (
+            latency.percentile(0.5),
+            latency.percentile(0.99),
+            latency.mean(),
+        ) {
+            (Some(p50), Some(p99), Some(mean)) => {
+                println!(
+                    "Detection Latency: p50 {} ms, p99 {} ms, mean {} ms (last {} of {} samples)",
+                    p50,
+                    p99,
+                    mean,
+                    latency.recent.len(),
+                    latency.count()
+                );
+                let histogram: Vec<String> = latency
+                    .buckets()
+                    .into_iter()
+                    .map(|(label, count)| format!("{}: {}", label, count))
+                    .collect();
+                println!("Latency Histogram: {}", histogram.join(", "));
+            }
+            _ => println!("Detection Latency: no samples"),
+        }
+        if self.missing_meta > 0 {
+            println!("Skipped Without Meta: {}", self.missing_meta);
+        }
+        if self.invalid_updates > 0 {
+            println!("Skipped Invalid Updates: {}", self.invalid_updates);
+        }
+        if self.duplicates > 0 {
+            println!("Skipped Duplicates: {}", self.duplicates);
+        }
+        if self.session.sampled_out > 0 {
+            println!("Dropped By Sampling: {}", self.session.sampled_out);
+        }
+        if self.gated_out > 0 {
+            println!("Held Back And Dropped: {}", self.gated_out);
+        }
+        self.session.print_instructions();
+
+        self.session.print_top(self.top_n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(latencies: impl IntoIterator<Item = i64>) -> LatencyHistogram {
+        let mut histogram = LatencyHistogram::default();
+        for latency_ms in latencies {
+            histogram.observe(latency_ms);
+        }
+        histogram
+    }
+
+    fn tally(keys: &[&'static str], max_keys: usize) -> Tally<&'static str> {
+        let mut tally = Tally {
+            counts: HashMap::new(),
+            max_keys,
+        };
+        for key in keys {
+            tally.observe(*key);
+        }
+        tally
+    }
+
+    #[test]
+    fn breaks_ties_by_key() {
+        let tally = tally(&["b", "c", "a", "c", "b", "d"], MAX_TALLY_KEYS);
+
+        assert_eq!(tally.top(3), [(&"b", 2), (&"c", 2), (&"a", 1)]);
+        assert_eq!(tally.top(0), []);
+        assert_eq!(tally.top(10).len(), 4);
+    }
+
+    #[test]
+    fn drops_the_least_frequent_keys_past_the_cap() {
+        let mut tally = tally(&["a", "a", "a", "b", "b", "c"], 4);
+        assert_eq!(tally.counts.len(), 3);
+
+        // a fifth key goes over, only the two most frequent are kept
+        tally.observe("d");
+        tally.observe("e");
+        assert_eq!(tally.top(10), [(&"a", 3), (&"b", 2)]);
+
+        tally.observe("c");
+        assert_eq!(tally.top(10), [(&"a", 3), (&"b", 2), (&"c", 1)]);
+    }
+
+    #[test]
+    fn buckets_latencies_up_to_each_bound() {
+        let histogram = histogram([0, 50, 51, 100, 10_000, 10_001]);
+        let buckets = histogram.buckets();
+
+        assert_eq!(buckets.len(), LATENCY_BUCKETS_MS.len() + 1);
+        assert_eq!(buckets[0], ("<=50ms".to_string(), 2));
+        assert_eq!(buckets[1], ("<=100ms".to_string(), 2));
+        assert_eq!(buckets[9], ("<=10000ms".to_string(), 1));
+        assert_eq!(buckets[10], (">10000ms".to_string(), 1));
+    }
+
+    #[test]
+    fn ranks_percentiles_to_the_nearest_sample() {
+        let histogram = histogram((1..=100).rev());
+
+        assert_eq!(histogram.percentile(0.0), Some(1));
+        // rank 49.5 rounds up
+        assert_eq!(histogram.percentile(0.5), Some(51));
+        assert_eq!(histogram.percentile(0.99), Some(99));
+        assert_eq!(histogram.percentile(1.0), Some(100));
+        assert_eq!(LatencyHistogram::default().percentile(0.5), None);
+    }
+
+    #[test]
+    fn keeps_percentiles_to_the_recent_window() {
+        let histogram = histogram(1..=LATENCY_WINDOW as i64 + 10);
+
+        assert_eq!(histogram.recent.len(), LATENCY_WINDOW);
+        assert_eq!(histogram.percentile(0.0), Some(11));
+        assert_eq!(histogram.count(), LATENCY_WINDOW as u64 + 10);
+    }
+
+    #[test]
+    fn averages_every_sample() {
+        assert_eq!(LatencyHistogram::default().mean(), None);
+        assert_eq!(histogram([1, 2]).mean(), Some(1));
+        // the whole session, not just the window, rounded down
+        let session = histogram((0..LATENCY_WINDOW as i64).map(|_| 0).chain([1_001, 2_000]));
+        assert_eq!(session.mean(), Some(2));
+    }
+}
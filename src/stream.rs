@@ -0,0 +1,231 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use futures_util::{Stream, StreamExt, future, stream};
+use tonic::Status;
+use tonic::transport::{Certificate, Identity};
+use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient, Interceptor};
+use yellowstone_grpc_proto::prelude::subscribe_update::UpdateOneof;
+use yellowstone_grpc_proto::prelude::{
+    CommitmentLevel, SubscribeRequest, SubscribeRequestFilterSlots,
+    SubscribeRequestFilterTransactions, SubscribeUpdate,
+};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::PumpfunError;
+use crate::parser::{
+    Commitment, PUMPFUN_PROGRAM_ID, PUMPSWAP_PROGRAM_ID, ParseOptions, PumpfunEvent,
+    parse_program_invocations_with,
+};
+
+/// PEM files for endpoints behind a private CA or requiring mutual TLS.
+/// Without a CA the platform's native roots are trusted.
+#[derive(Clone, Debug, Default)]
+pub struct TlsOptions {
+    pub ca_cert: Option<PathBuf>,
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+}
+
+impl TlsOptions {
+    fn client_tls_config(&self) -> Result<ClientTlsConfig, PumpfunError> {
+        let mut tls_config = match &self.ca_cert {
+            Some(path) => {
+                ClientTlsConfig::new().ca_certificate(Certificate::from_pem(read_pem(path)?))
+            }
+            None => ClientTlsConfig::new().with_native_roots(),
+        };
+
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => {
+                tls_config =
+                    tls_config.identity(Identity::from_pem(read_pem(cert)?, read_pem(key)?));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(PumpfunError::Connect(
+                    "a client certificate and key must be given together".into(),
+                ));
+            }
+        }
+
+        Ok(tls_config)
+    }
+}
+
+fn read_pem(path: &Path) -> Result<Vec<u8>, PumpfunError> {
+    fs::read(path).map_err(|e| {
+        PumpfunError::Connect(format!("failed to read {}: {}", path.display(), e).into())
+    })
+}
+
+pub async fn connect(
+    endpoint: &str,
+    x_token: Option<&str>,
+    tls: &TlsOptions,
+) -> Result<GeyserGrpcClient<impl Interceptor + use<>>, PumpfunError> {
+    GeyserGrpcClient::build_from_shared(endpoint.to_string())
+        .map_err(|e| PumpfunError::Connect(e.into()))?
+        .x_token(x_token)
+        .map_err(|e| PumpfunError::Connect(e.into()))?
+        .tls_config(tls.client_tls_config()?)
+        .map_err(|e| PumpfunError::Connect(e.into()))?
+        .connect()
+        .await
+        .map_err(|e| PumpfunError::Connect(e.into()))
+}
+
+/// Non-vote, successful transactions that touch the Pumpfun program.
+pub fn pumpfun_subscribe_request() -> SubscribeRequest {
+    programs_subscribe_request(&[PUMPFUN_PROGRAM_ID])
+}
+
+/// Non-vote, successful transactions that touch any of `programs`.
+pub fn programs_subscribe_request(programs: &[Pubkey]) -> SubscribeRequest {
+    transactions_request(
+        programs.iter().map(|program| program.to_string()).collect(),
+        vec![],
+    )
+}
+
+/// `pumpfun_subscribe_request` replayed from `from_slot`, for providers that
+/// keep history. Slot updates are included so callers can tell when a range
+/// has passed even if it holds no Pumpfun transactions.
+pub fn backfill_subscribe_request(from_slot: u64) -> SubscribeRequest {
+    let mut subscribe_request = pumpfun_subscribe_request();
+    subscribe_request.slots.insert(
+        "pumpfun".to_string(),
+        SubscribeRequestFilterSlots {
+            filter_by_commitment: Some(true),
+            interslot_updates: Some(false),
+        },
+    );
+    subscribe_request.from_slot = Some(from_slot);
+    subscribe_request
+}
+
+/// Pumpfun transactions that touch any of `mints`, i.e. their trades.
+pub fn trades_subscribe_request(mints: &[Pubkey]) -> SubscribeRequest {
+    transactions_request(
+        mints.iter().map(|mint| mint.to_string()).collect(),
+        vec![PUMPFUN_PROGRAM_ID.to_string()],
+    )
+}
+
+/// Every transaction touching `mint`, on the bonding curve and, once
+/// migrated, on pump-swap. The subscription for `follow_mint`.
+pub fn mint_subscribe_request(mint: &Pubkey) -> SubscribeRequest {
+    transactions_request(vec![mint.to_string()], vec![])
+}
+
+fn transactions_request(
+    account_include: Vec<String>,
+    account_required: Vec<String>,
+) -> SubscribeRequest {
+    let mut subscribe_request = SubscribeRequest::default();
+    subscribe_request.transactions.insert(
+        "pumpfun".to_string(),
+        SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: Some(false),
+            signature: None,
+            account_include,
+            account_exclude: vec![],
+            account_required,
+        },
+    );
+    subscribe_request
+}
+
+/// Sets the commitment `request` is served at; `None` keeps the provider's default.
+pub fn with_commitment(
+    mut subscribe_request: SubscribeRequest,
+    commitment: Option<Commitment>,
+) -> SubscribeRequest {
+    subscribe_request.commitment = commitment.map(|commitment| {
+        let level = match commitment {
+            Commitment::Processed => CommitmentLevel::Processed,
+            Commitment::Confirmed => CommitmentLevel::Confirmed,
+            Commitment::Finalized => CommitmentLevel::Finalized,
+        };
+        level as i32
+    });
+    subscribe_request
+}
+
+// the stream's items are tonic's own `Result<_, Status>`
+#[allow(clippy::result_large_err)]
+pub async fn subscribe<F: Interceptor>(
+    client: &mut GeyserGrpcClient<F>,
+    request: SubscribeRequest,
+) -> Result<impl Stream<Item = Result<SubscribeUpdate, Status>> + use<F>, PumpfunError> {
+    let (subscribe_tx, subscribe_stream) = client
+        .subscribe_with_request(Some(request))
+        .await
+        .map_err(|e| PumpfunError::Subscribe(e.into()))?;
+
+    // the request half must outlive the stream, dropping it ends the subscription
+    Ok(subscribe_stream.map(move |update| {
+        let _ = &subscribe_tx;
+        update
+    }))
+}
+
+/// One mint's lifecycle out of `updates`, in order: its create (if it's still
+/// to come), trades on the bonding curve, the migration's pool and the swaps
+/// on that pool. Everything else is left out. Ends with `updates` or at its
+/// first error; subscribe with `mint_subscribe_request` to only be sent
+/// the mint's transactions.
+pub fn follow_mint<S>(updates: S, mint: Pubkey) -> impl Stream<Item = PumpfunEvent>
+where
+    S: Stream<Item = Result<SubscribeUpdate, Status>>,
+{
+    // set by the migration, swaps name the pool rather than the mint
+    let mut pool = None;
+    updates
+        .take_while(|update| future::ready(update.is_ok()))
+        .filter_map(|update| future::ready(update.ok()))
+        .flat_map(move |update| stream::iter(mint_events(&update, &mint, &mut pool)))
+}
+
+fn mint_events(
+    update: &SubscribeUpdate,
+    mint: &Pubkey,
+    pool: &mut Option<Pubkey>,
+) -> Vec<PumpfunEvent> {
+    let Some(UpdateOneof::Transaction(transaction)) = &update.update_oneof else {
+        return vec![];
+    };
+    let Some(meta) = transaction
+        .transaction
+        .as_ref()
+        .and_then(|transaction| transaction.meta.as_ref())
+    else {
+        return vec![];
+    };
+    if meta.err.is_some() {
+        return vec![];
+    }
+
+    let programs = [PUMPFUN_PROGRAM_ID, PUMPSWAP_PROGRAM_ID];
+    let Ok(invocations) =
+        parse_program_invocations_with(&meta.log_messages, &programs, &ParseOptions::default())
+    else {
+        return vec![];
+    };
+    invocations
+        .into_iter()
+        .map(|invocation| invocation.event)
+        .filter(|event| match event {
+            PumpfunEvent::Create(token_info) => token_info.mint == *mint,
+            PumpfunEvent::Trade(trade_info) => trade_info.mint == *mint,
+            PumpfunEvent::AmmCreatePool(pool_info) if pool_info.base_mint == *mint => {
+                *pool = Some(pool_info.pool);
+                true
+            }
+            PumpfunEvent::AmmCreatePool(_) => false,
+            PumpfunEvent::AmmSwap(swap_info) => *pool == Some(swap_info.pool),
+        })
+        .collect()
+}
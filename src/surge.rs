@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use log::warn;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+
+use crate::cli::StreamArgs;
+use crate::control::ControlEvent;
+
+// launches are counted per minute, the baseline moves once a minute
+const BUCKET: Duration = Duration::from_secs(60);
+// minutes counted into the baseline before it can be surged past
+const WARMUP_BUCKETS: u32 = 5;
+
+/// `--surge-multiplier`: compares each minute's launch count against an
+/// EWMA of the minutes before it and raises a `LaunchSurge` event when the
+/// count passes `multiplier` times the baseline. One event per surge, the
+/// detector re-arms once a minute comes back under the threshold.
+pub struct SurgeDetector {
+    multiplier: f64,
+    alpha: f64,
+    // a quiet baseline of 0.2/min would otherwise surge on a single launch
+    min_rate: u64,
+    baseline: Option<f64>,
+    buckets: u32,
+    launches: u64,
+    surging: bool,
+    // `--surge-webhook`, also POSTed each event
+    webhook: Option<(reqwest::Client, String)>,
+}
+
+impl SurgeDetector {
+    pub fn from_args(args: &StreamArgs) -> Option<Self> {
+        Some(Self {
+            multiplier: args.surge_multiplier?,
+            alpha: args.surge_alpha,
+            min_rate: args.surge_min_rate,
+            baseline: None,
+            buckets: 0,
+            launches: 0,
+            surging: false,
+            webhook: args
+                .surge_webhook
+                .clone()
+                .map(|url| (reqwest::Client::new(), url)),
+        })
+    }
+
+    /// The per-minute tick, first firing a minute in rather than straight away.
+    pub fn interval() -> Interval {
+        let mut interval = tokio::time::interval_at(Instant::now() + BUCKET, BUCKET);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        interval
+    }
+
+    pub fn record_launch(&mut self) {
+        self.launches += 1;
+    }
+
+    /// Closes the current minute, folding it into the baseline, and returns
+    /// a `LaunchSurge` if it started a surge.
+    pub fn check(&mut self) -> Option<ControlEvent> {
+        let rate = std::mem::take(&mut self.launches);
+        let baseline = self.baseline;
+        self.buckets += 1;
+        self.baseline = Some(match baseline {
+            Some(baseline) => self.alpha * rate as f64 + (1.0 - self.alpha) * baseline,
+            None => rate as f64,
+        });
+
+        let baseline = baseline.filter(|_| self.buckets > WARMUP_BUCKETS)?;
+        let threshold = baseline * self.multiplier;
+        if rate < self.min_rate || rate as f64 <= threshold {
+            self.surging = false;
+            return None;
+        }
+        if std::mem::replace(&mut self.surging, true) {
+            return None;
+        }
+
+        warn!(
+            "Launch surge: {} launches in the last minute, baseline {:.1}/min",
+            rate, baseline
+        );
+        let event = ControlEvent::LaunchSurge {
+            at: Utc::now(),
+            launches_per_minute: rate,
+            baseline_per_minute: baseline,
+            multiplier: self.multiplier,
+        };
+        if let Some((http, url)) = &self.webhook {
+            spawn_webhook(http.clone(), url.clone(), event.clone());
+        }
+        Some(event)
+    }
+}
+
+// off the pipeline, a slow endpoint shouldn't hold up launches
+fn spawn_webhook(http: reqwest::Client, url: String, event: ControlEvent) {
+    tokio::spawn(async move {
+        let posted = async {
+            http.post(&url)
+                .json(&event)
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok::<_, reqwest::Error>(())
+        };
+        if let Err(e) = posted.await {
+            warn!("Failed to POST launch surge to {}: {:?}", url, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> SurgeDetector {
+        SurgeDetector {
+            multiplier: 3.0,
+            alpha: 0.2,
+            min_rate: 10,
+            baseline: None,
+            buckets: 0,
+            launches: 0,
+            surging: false,
+            webhook: None,
+        }
+    }
+
+    // a minute with this many launches
+    fn minute(detector: &mut SurgeDetector, launches: u64) -> Option<ControlEvent> {
+        for _ in 0..launches {
+            detector.record_launch();
+        }
+        detector.check()
+    }
+
+    fn warmed_up(launches_per_minute: u64) -> SurgeDetector {
+        let mut detector = detector();
+        for _ in 0..WARMUP_BUCKETS {
+            assert!(minute(&mut detector, launches_per_minute).is_none());
+        }
+        detector
+    }
+
+    #[test]
+    fn stays_quiet_while_warming_up() {
+        let mut detector = detector();
+        assert!(minute(&mut detector, 10).is_none());
+        for _ in 1..WARMUP_BUCKETS {
+            assert!(minute(&mut detector, 1_000).is_none());
+        }
+    }
+
+    #[test]
+    fn ignores_a_surge_under_the_min_rate() {
+        let mut detector = warmed_up(1);
+
+        // five times the baseline, but under 10/min
+        assert!(minute(&mut detector, 5).is_none());
+        assert!(minute(&mut detector, 12).is_some());
+    }
+
+    #[test]
+    fn raises_one_event_per_surge() {
+        let mut detector = warmed_up(10);
+
+        let Some(ControlEvent::LaunchSurge {
+            launches_per_minute,
+            baseline_per_minute,
+            ..
+        }) = minute(&mut detector, 50)
+        else {
+            panic!("no surge");
+        };
+        assert_eq!(launches_per_minute, 50);
+        assert_eq!(baseline_per_minute, 10.0);
+
+        // still over the risen baseline's threshold, the same surge
+        assert!(minute(&mut detector, 60).is_none());
+        // back under, re-armed for the next one
+        assert!(minute(&mut detector, 10).is_none());
+        assert!(minute(&mut detector, 100).is_some());
+    }
+}
@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use log::debug;
+
+use solana_pumpfun::parser::CreateTokenInfo;
+use solana_pumpfun::utils::{OutputFormat, read_records};
+
+use crate::cli::StreamArgs;
+use crate::pipeline::Pipeline;
+
+// how often `--follow` re-reads the log
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Sends the records of the `--tail` log through the pipeline's filters and
+/// sinks, then with `--follow` the records added to it afterwards.
+pub async fn tail_launches(args: StreamArgs, path: String) -> anyhow::Result<()> {
+    let mut pipeline = Pipeline::from_args(&args)?;
+    let format = args.format;
+
+    let records = read_log(&path, format).await?;
+    let mut seen = records.len();
+    for token_info in &records {
+        pipeline.replay(token_info);
+    }
+    println!("Replayed {} records from {}", seen, path);
+
+    if args.follow {
+        println!("Following {}", path);
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = pipeline.tick() => continue,
+                _ = tokio::signal::ctrl_c() => break,
+            }
+
+            // the JSON log is rewritten in place, a read can catch it half written
+            let records = match read_log(&path, format).await {
+                Ok(records) => records,
+                Err(e) => {
+                    debug!("Skipping unreadable {}: {:?}", path, e);
+                    continue;
+                }
+            };
+            // fewer records than before means the log was replaced, start over
+            if records.len() < seen {
+                seen = 0;
+            }
+            for token_info in &records[seen..] {
+                pipeline.replay(token_info);
+            }
+            seen = records.len();
+        }
+    }
+
+    pipeline.stats.print_summary();
+    pipeline.close().await;
+
+    Ok(())
+}
+
+async fn read_log(path: &str, format: OutputFormat) -> anyhow::Result<Vec<CreateTokenInfo>> {
+    let path = path.to_string();
+    tokio::task::spawn_blocking(move || read_records(&path, format)).await?
+}
@@ -0,0 +1,123 @@
+use solana_pumpfun::parser::CreateTokenInfo;
+use solana_sdk::signature::Signature;
+
+use crate::display::PubkeyStyle;
+
+const PLACEHOLDERS: &[&str] = &[
+    "name",
+    "symbol",
+    "mint",
+    "creator",
+    "bonding_curve",
+    "uri",
+    "slot",
+    "price",
+    "market_cap",
+    "signature",
+    "latency",
+];
+
+#[derive(Clone, Debug)]
+enum Part {
+    Text(String),
+    Field(&'static str),
+}
+
+/// `--template`: one launch per rendering, e.g. `{symbol} {mint} by {creator}`.
+/// `{{`/`}}` are literal braces, `\n` and `\t` a newline and tab.
+#[derive(Clone, Debug)]
+pub struct LaunchTemplate {
+    parts: Vec<Part>,
+}
+
+impl LaunchTemplate {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut chars = value.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    chars.next();
+                    text.push(c);
+                }
+                ('\\', Some('n')) => {
+                    chars.next();
+                    text.push('\n');
+                }
+                ('\\', Some('t')) => {
+                    chars.next();
+                    text.push('\t');
+                }
+                ('{', _) => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("unclosed placeholder {{{}", name)),
+                        }
+                    }
+                    let Some(field) = PLACEHOLDERS.iter().find(|field| **field == name) else {
+                        return Err(format!(
+                            "unknown placeholder {{{}}}, expected one of: {}",
+                            name,
+                            PLACEHOLDERS.join(", ")
+                        ));
+                    };
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                ('}', _) => return Err("unmatched `}`, use `}}` for a literal brace".to_string()),
+                _ => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    pub fn render(
+        &self,
+        token_info: &CreateTokenInfo,
+        signature: &Signature,
+        pubkeys: PubkeyStyle,
+    ) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Field(field) => field_value(field, token_info, signature, pubkeys),
+            })
+            .collect()
+    }
+}
+
+fn field_value(
+    field: &str,
+    token_info: &CreateTokenInfo,
+    signature: &Signature,
+    pubkeys: PubkeyStyle,
+) -> String {
+    match field {
+        "name" => token_info.name.clone(),
+        "symbol" => token_info.symbol.clone(),
+        "mint" => pubkeys.show(&token_info.mint),
+        "creator" => pubkeys.show(&token_info.user),
+        "bonding_curve" => pubkeys.show(&token_info.bonding_curve),
+        "uri" => token_info.uri.clone(),
+        "slot" => token_info.slot.to_string(),
+        "price" => format!("{:.10}", token_info.initial_price_sol()),
+        "market_cap" => format!("{:.2}", token_info.initial_market_cap_sol()),
+        "signature" => signature.to_string(),
+        "latency" => token_info
+            .detection_latency_ms
+            .map_or("n/a".to_string(), |ms| format!("{} ms", ms)),
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,399 @@
+//! Opt-in buying of freshly detected launches. Kept apart from the indexer so
+//! nothing here runs unless the CLI explicitly enables it.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcSendTransactionConfig;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer, read_keypair_file},
+    system_program,
+    transaction::Transaction,
+};
+
+use crate::parser::{
+    CreateTokenInfo, LAMPORTS_PER_SOL, PUMPFUN_INITIAL_VIRTUAL_SOL_RESERVES,
+    PUMPFUN_INITIAL_VIRTUAL_TOKEN_RESERVES, PUMPFUN_PROGRAM_ID,
+};
+
+const PUMPFUN_GLOBAL: Pubkey = pubkey!("4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf");
+const PUMPFUN_FEE_RECIPIENT: Pubkey = pubkey!("CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM");
+const PUMPFUN_EVENT_AUTHORITY: Pubkey = pubkey!("Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1");
+pub(crate) const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+
+#[derive(Clone, Debug)]
+pub struct BuyParams {
+    pub sol_amount: f64,
+    pub slippage_bps: u16,
+    // micro-lamports per compute unit
+    pub priority_fee: u64,
+    pub compute_unit_limit: u32,
+}
+
+/// Caps over every buy an `AutoBuyer` makes, None for no cap.
+#[derive(Clone, Debug, Default)]
+pub struct BuyLimits {
+    pub max_buys: Option<u64>,
+    pub max_spend_sol: Option<f64>,
+}
+
+pub struct AutoBuyer {
+    rpc: RpcClient,
+    keypair: Keypair,
+    params: BuyParams,
+    max_buys: Option<u64>,
+    max_spend_lamports: Option<u64>,
+    buys: AtomicU64,
+    // each buy at its max_sol_cost, what it can spend at worst
+    committed_lamports: AtomicU64,
+}
+
+impl AutoBuyer {
+    pub fn new(
+        rpc_url: &str,
+        keypair_path: &str,
+        params: BuyParams,
+        limits: BuyLimits,
+    ) -> anyhow::Result<Self> {
+        if !(params.sol_amount.is_finite() && params.sol_amount > 0.0) {
+            anyhow::bail!("Buy amount must be above 0 SOL, got {}", params.sol_amount);
+        }
+        let keypair = read_keypair_file(keypair_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read keypair {}: {}", keypair_path, e))?;
+
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::processed()),
+            keypair,
+            params,
+            max_buys: limits.max_buys,
+            max_spend_lamports: limits
+                .max_spend_sol
+                .map(|sol| (sol * LAMPORTS_PER_SOL) as u64),
+            buys: AtomicU64::new(0),
+            committed_lamports: AtomicU64::new(0),
+        })
+    }
+
+    /// Counts one more buy against the limits, false (counting nothing) once
+    /// it would pass `max_buys` or `max_spend_sol`. Call before each `buy`.
+    pub fn reserve(&self) -> bool {
+        let cost = buy_lamports(&self.params).1;
+        let buys = self
+            .buys
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |buys| {
+                (self.max_buys.is_none_or(|max| buys < max)).then_some(buys + 1)
+            });
+        if buys.is_err() {
+            return false;
+        }
+        let committed =
+            self.committed_lamports
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |committed| {
+                    let committed = committed.checked_add(cost)?;
+                    self.max_spend_lamports
+                        .is_none_or(|max| committed <= max)
+                        .then_some(committed)
+                });
+        if committed.is_err() {
+            self.buys.fetch_sub(1, Ordering::SeqCst);
+            return false;
+        }
+        true
+    }
+
+    /// Buys counted so far and the SOL they may spend.
+    pub fn committed(&self) -> (u64, f64) {
+        (
+            self.buys.load(Ordering::SeqCst),
+            self.committed_lamports.load(Ordering::SeqCst) as f64 / LAMPORTS_PER_SOL,
+        )
+    }
+
+    pub fn wallet(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    /// Builds, signs and submits a buy of `token_info`'s bonding curve.
+    pub async fn buy(&self, token_info: &CreateTokenInfo) -> anyhow::Result<Signature> {
+        let instructions = buy_instructions(&self.keypair.pubkey(), token_info, &self.params);
+        let blockhash = self
+            .rpc
+            .get_latest_blockhash()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get blockhash: {}", e))?;
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.keypair.pubkey()),
+            &[&self.keypair],
+            blockhash,
+        );
+
+        self.rpc
+            .send_transaction_with_config(
+                &transaction,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send buy: {}", e))
+    }
+}
+
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Tokens bought for `sol_in` lamports on a curve with the given virtual reserves.
+pub fn tokens_out(sol_in: u64, virtual_sol_reserves: u64, virtual_token_reserves: u64) -> u64 {
+    let k = virtual_sol_reserves as u128 * virtual_token_reserves as u128;
+    let new_token_reserves = k / (virtual_sol_reserves as u128 + sol_in as u128) + 1;
+    (virtual_token_reserves as u128).saturating_sub(new_token_reserves) as u64
+}
+
+// lamports a buy puts in, and the most it may cost with slippage
+fn buy_lamports(params: &BuyParams) -> (u64, u64) {
+    let sol_in = (params.sol_amount * LAMPORTS_PER_SOL) as u64;
+    (
+        sol_in,
+        sol_in + sol_in * params.slippage_bps as u64 / 10_000,
+    )
+}
+
+/// Instructions for a buy at the launch's initial curve price. `max_sol_cost`
+/// absorbs the slippage from earlier buys in the same slots.
+pub fn buy_instructions(
+    user: &Pubkey,
+    token_info: &CreateTokenInfo,
+    params: &BuyParams,
+) -> Vec<Instruction> {
+    let (sol_in, max_sol_cost) = buy_lamports(params);
+    let amount = tokens_out(
+        sol_in,
+        PUMPFUN_INITIAL_VIRTUAL_SOL_RESERVES,
+        PUMPFUN_INITIAL_VIRTUAL_TOKEN_RESERVES,
+    );
+
+    let associated_bonding_curve =
+        associated_token_address(&token_info.bonding_curve, &token_info.mint);
+    let associated_user = associated_token_address(user, &token_info.mint);
+    let (creator_vault, _) = Pubkey::find_program_address(
+        &[b"creator-vault", token_info.user.as_ref()],
+        &PUMPFUN_PROGRAM_ID,
+    );
+
+    let mut data = BUY_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&max_sol_cost.to_le_bytes());
+
+    vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(params.compute_unit_limit),
+        ComputeBudgetInstruction::set_compute_unit_price(params.priority_fee),
+        // idempotent create of the buyer's token account
+        Instruction {
+            program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*user, true),
+                AccountMeta::new(associated_user, false),
+                AccountMeta::new_readonly(*user, false),
+                AccountMeta::new_readonly(token_info.mint, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+            data: vec![1],
+        },
+        Instruction {
+            program_id: PUMPFUN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(PUMPFUN_GLOBAL, false),
+                AccountMeta::new(PUMPFUN_FEE_RECIPIENT, false),
+                AccountMeta::new_readonly(token_info.mint, false),
+                AccountMeta::new(token_info.bonding_curve, false),
+                AccountMeta::new(associated_bonding_curve, false),
+                AccountMeta::new(associated_user, false),
+                AccountMeta::new(*user, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new(creator_vault, false),
+                AccountMeta::new_readonly(PUMPFUN_EVENT_AUTHORITY, false),
+                AccountMeta::new_readonly(PUMPFUN_PROGRAM_ID, false),
+            ],
+            data,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::write_keypair_file;
+
+    use super::*;
+
+    fn params(sol_amount: f64, slippage_bps: u16) -> BuyParams {
+        BuyParams {
+            sol_amount,
+            slippage_bps,
+            priority_fee: 1_000,
+            compute_unit_limit: 100_000,
+        }
+    }
+
+    fn auto_buyer(params: BuyParams, limits: BuyLimits) -> AutoBuyer {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keypair.json");
+        write_keypair_file(&Keypair::new(), &path).unwrap();
+        AutoBuyer::new(
+            "http://127.0.0.1:8899",
+            path.to_str().unwrap(),
+            params,
+            limits,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn stops_at_max_buys() {
+        let auto_buyer = auto_buyer(
+            params(0.1, 1_000),
+            BuyLimits {
+                max_buys: Some(2),
+                max_spend_sol: None,
+            },
+        );
+
+        let reserved: Vec<_> = (0..3).map(|_| auto_buyer.reserve()).collect();
+
+        assert_eq!(reserved, [true, true, false]);
+        assert_eq!(auto_buyer.committed(), (2, 0.22));
+    }
+
+    // 0.1 SOL at 10% slippage is up to 0.11 SOL, so only one fits in 0.2
+    #[test]
+    fn stops_at_max_spend_counting_slippage() {
+        let auto_buyer = auto_buyer(
+            params(0.1, 1_000),
+            BuyLimits {
+                max_buys: None,
+                max_spend_sol: Some(0.2),
+            },
+        );
+
+        assert!(auto_buyer.reserve());
+        assert!(!auto_buyer.reserve());
+        assert_eq!(auto_buyer.committed(), (1, 0.11));
+    }
+
+    #[test]
+    fn gives_back_the_buy_the_spend_cap_refuses() {
+        let auto_buyer = auto_buyer(
+            params(0.1, 1_000),
+            BuyLimits {
+                max_buys: Some(3),
+                max_spend_sol: Some(0.25),
+            },
+        );
+
+        assert!(auto_buyer.reserve());
+        assert!(auto_buyer.reserve());
+        assert!(!auto_buyer.reserve());
+        assert!(!auto_buyer.reserve());
+        assert_eq!(auto_buyer.committed(), (2, 0.22));
+    }
+
+    #[test]
+    fn rejects_a_buy_of_nothing() {
+        for sol_amount in [0.0, -1.0, f64::NAN] {
+            let built = AutoBuyer::new(
+                "http://127.0.0.1:8899",
+                "unused.json",
+                params(sol_amount, 0),
+                BuyLimits::default(),
+            );
+            assert!(built.is_err(), "{}", sol_amount);
+        }
+    }
+
+    #[test]
+    fn adds_slippage_to_the_max_cost() {
+        assert_eq!(buy_lamports(&params(0.5, 100)), (500_000_000, 505_000_000));
+        assert_eq!(
+            buy_lamports(&params(1.0, 0)),
+            (1_000_000_000, 1_000_000_000)
+        );
+    }
+
+    #[test]
+    fn prices_tokens_on_the_curve() {
+        // k = 100_000, 100_000 / 110 = 909, rounded up against the buyer to 910
+        assert_eq!(tokens_out(10, 100, 1_000), 90);
+        assert_eq!(
+            tokens_out(
+                1_000_000_000,
+                PUMPFUN_INITIAL_VIRTUAL_SOL_RESERVES,
+                PUMPFUN_INITIAL_VIRTUAL_TOKEN_RESERVES
+            ),
+            34_612_903_225_806
+        );
+        assert_eq!(tokens_out(0, 100, 1_000), 0);
+    }
+
+    #[test]
+    fn builds_the_buy_instruction() {
+        let user = Pubkey::new_unique();
+        let token_info = CreateTokenInfo {
+            mint: Pubkey::new_unique(),
+            bonding_curve: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            ..Default::default()
+        };
+
+        let instructions = buy_instructions(&user, &token_info, &params(1.0, 100));
+
+        assert_eq!(instructions.len(), 4);
+        let buy = &instructions[3];
+        assert_eq!(buy.program_id, PUMPFUN_PROGRAM_ID);
+        let associated_user = associated_token_address(&user, &token_info.mint);
+        let accounts: Vec<_> = buy
+            .accounts
+            .iter()
+            .map(|meta| (meta.pubkey, meta.is_writable, meta.is_signer))
+            .collect();
+        assert_eq!(
+            accounts[..7],
+            [
+                (PUMPFUN_GLOBAL, false, false),
+                (PUMPFUN_FEE_RECIPIENT, true, false),
+                (token_info.mint, false, false),
+                (token_info.bonding_curve, true, false),
+                (
+                    associated_token_address(&token_info.bonding_curve, &token_info.mint),
+                    true,
+                    false
+                ),
+                (associated_user, true, false),
+                (user, true, true),
+            ]
+        );
+        assert_eq!(accounts.len(), 12);
+        assert_eq!(accounts[11], (PUMPFUN_PROGRAM_ID, false, false));
+
+        let mut data = BUY_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&34_612_903_225_806u64.to_le_bytes());
+        data.extend_from_slice(&1_010_000_000u64.to_le_bytes());
+        assert_eq!(buy.data, data);
+        assert_eq!(instructions[2].accounts[1].pubkey, associated_user);
+    }
+}
@@ -0,0 +1,247 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use log::warn;
+use solana_pumpfun::parser::CreateTokenInfo;
+use tokio::sync::mpsc as async_mpsc;
+use tracing::{Span, info_span};
+
+/// `--transform`: a long-running command that gets each launch as one line
+/// of JSON on stdin and answers with one line on stdout, the record to write
+/// (changed or not, computed fields go in `extra`) or `null` to drop it.
+///
+/// A command that errors, answers garbage or takes longer than the timeout
+/// is restarted for the next launch, and the launch goes out untouched.
+pub struct Transform {
+    command: String,
+    timeout: Duration,
+    // None after a failure, respawned on the next launch
+    process: Option<Process>,
+}
+
+/// A `Transform` on a thread of its own, as the command is a blocking call
+/// per launch. Launches are answered through `recv` in the order sent.
+pub struct TransformWorker {
+    // None once closed
+    tx: Option<mpsc::Sender<(CreateTokenInfo, Span)>>,
+    rx: async_mpsc::UnboundedReceiver<Transformed>,
+}
+
+/// A launch back from `--transform`.
+pub struct Transformed {
+    // as it was sent
+    pub token_info: CreateTokenInfo,
+    // what to write instead, None to drop it
+    pub transformed: Option<CreateTokenInfo>,
+    pub span: Span,
+}
+
+impl TransformWorker {
+    pub fn spawn(mut transform: Transform) -> Self {
+        let (tx, requests) = mpsc::channel::<(CreateTokenInfo, Span)>();
+        let (answers, rx) = async_mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            while let Ok((token_info, span)) = requests.recv() {
+                let transformed = info_span!(parent: &span, "transform")
+                    .in_scope(|| transform.apply(&token_info));
+                let answer = Transformed {
+                    token_info,
+                    transformed,
+                    span,
+                };
+                if answers.send(answer).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { tx: Some(tx), rx }
+    }
+
+    pub fn send(&self, token_info: CreateTokenInfo, span: Span) {
+        if let Some(tx) = &self.tx
+            && tx.send((token_info, span)).is_err()
+        {
+            warn!("--transform worker stopped, dropping a launch");
+        }
+    }
+
+    pub async fn recv(&mut self) -> Option<Transformed> {
+        self.rx.recv().await
+    }
+
+    /// Takes no more launches, `recv` ends once those sent are answered.
+    pub fn close(&mut self) {
+        self.tx = None;
+    }
+}
+
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+    // lines from a reader thread, so a hung command can be timed out
+    lines: mpsc::Receiver<std::io::Result<String>>,
+}
+
+impl Transform {
+    /// Starts `command` under `sh -c`, failing if it can't be spawned.
+    pub fn spawn(command: &str, timeout: Duration) -> anyhow::Result<Self> {
+        Ok(Self {
+            command: command.to_string(),
+            timeout,
+            process: Some(Process::spawn(command)?),
+        })
+    }
+
+    /// The launch to write in place of `token_info`, None to drop it.
+    pub fn apply(&mut self, token_info: &CreateTokenInfo) -> Option<CreateTokenInfo> {
+        match self.call(token_info) {
+            Ok(transformed) => transformed,
+            Err(e) => {
+                warn!(
+                    "--transform failed on {}, writing it unchanged: {:?}",
+                    token_info.mint, e
+                );
+                // its next answer could be this launch's, start over
+                self.process = None;
+                Some(token_info.clone())
+            }
+        }
+    }
+
+    fn call(&mut self, token_info: &CreateTokenInfo) -> anyhow::Result<Option<CreateTokenInfo>> {
+        let process = match &mut self.process {
+            Some(process) => process,
+            None => self.process.insert(Process::spawn(&self.command)?),
+        };
+
+        let mut request = serde_json::to_vec(token_info)?;
+        request.push(b'\n');
+        process.stdin.write_all(&request)?;
+        process.stdin.flush()?;
+
+        let line = match process.lines.recv_timeout(self.timeout) {
+            Ok(line) => line?,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                anyhow::bail!("no answer within {:?}", self.timeout)
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => anyhow::bail!("command exited"),
+        };
+        serde_json::from_str(&line)
+            .map_err(|e| anyhow::anyhow!("expected a launch record or null, got {:?}: {}", line, e))
+    }
+}
+
+impl Process {
+    fn spawn(command: &str) -> anyhow::Result<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start --transform {:?}: {}", command, e))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+        })
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        // the reader thread ends with the command's stdout
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    fn launch() -> CreateTokenInfo {
+        CreateTokenInfo {
+            name: "Moon Cat".to_string(),
+            symbol: "MCAT".to_string(),
+            mint: Pubkey::new_unique(),
+            ..Default::default()
+        }
+    }
+
+    // answers every line with `answer`
+    fn answering(answer: &str) -> Transform {
+        Transform::spawn(
+            &format!("while read line; do echo '{}'; done", answer),
+            TIMEOUT,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn drops_a_launch_answered_with_null() {
+        let mut transform = answering("null");
+
+        assert_eq!(transform.apply(&launch()), None);
+        assert!(transform.process.is_some());
+    }
+
+    #[test]
+    fn passes_a_launch_through_and_restarts_on_garbage() {
+        let mut transform = answering("not json");
+        let token_info = launch();
+
+        assert_eq!(transform.apply(&token_info), Some(token_info.clone()));
+        assert!(transform.process.is_none());
+        // respawned for the next one
+        assert_eq!(transform.apply(&token_info), Some(token_info));
+    }
+
+    #[test]
+    fn times_out_a_hung_command() {
+        let mut transform = Transform::spawn("sleep 30", TIMEOUT).unwrap();
+        let token_info = launch();
+
+        let started = Instant::now();
+        assert_eq!(transform.apply(&token_info), Some(token_info));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(transform.process.is_none());
+    }
+
+    // on a current-thread runtime, where blocking in place would panic
+    #[tokio::test]
+    async fn answers_in_order_off_the_runtime() {
+        let mut worker = TransformWorker::spawn(Transform::spawn("cat", TIMEOUT).unwrap());
+        let launches = [launch(), launch(), launch()];
+        for token_info in &launches {
+            worker.send(token_info.clone(), Span::none());
+        }
+        worker.close();
+
+        let mut answered = vec![];
+        while let Some(answer) = worker.recv().await {
+            answered.push(answer.transformed.unwrap().mint);
+        }
+        let sent: Vec<_> = launches.iter().map(|token_info| token_info.mint).collect();
+        assert_eq!(answered, sent);
+    }
+}
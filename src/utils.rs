@@ -1,181 +1,377 @@
-use base64::{Engine as _, engine::general_purpose::STANDARD as base64};
-use borsh::{BorshDeserialize, BorshSerialize};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use log::warn;
 use serde::{Deserialize, Serialize};
-use serde_with::{DisplayFromStr, serde_as};
-use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
 use solana_transaction_status::EncodedTransactionWithStatusMeta;
 use solana_transaction_status::UiTransactionEncoding;
-use std::fs::File;
-use std::io::{Read, Write};
-use std::str::FromStr;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Write};
+use std::path::Path;
 use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
 
+use crate::error::PumpfunError;
+use crate::parser::CreateTokenInfo;
+use crate::records::{
+    BorshRecordReader, read_borsh_header, write_borsh_header, write_borsh_record,
+};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// one pretty-printed JSON document with a `results` array
+    #[default]
+    Json,
+    /// length-prefixed Borsh records after a versioned header, appended
+    Borsh,
+}
+
+impl OutputFormat {
+    pub fn default_path(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "create_token_log.json",
+            OutputFormat::Borsh => "create_token_log.borsh",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Borsh => "borsh",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct TransactionPretty {
     pub slot: u64,
+    // position of the transaction within its block
+    pub index: u64,
     pub signature: Signature,
     pub is_vote: bool,
     pub tx: EncodedTransactionWithStatusMeta,
+    // provider's timestamp for the update, if it sends one
+    pub provider_created_at: Option<DateTime<Utc>>,
 }
 
-#[serde_as]
-#[derive(
-    Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, Serialize, Deserialize,
-)]
-pub struct CreateTokenInfo {
-    pub name: String,
-    pub symbol: String,
-    pub uri: String,
-    #[serde_as(as = "DisplayFromStr")]
-    pub mint: Pubkey,
-    #[serde_as(as = "DisplayFromStr")]
-    pub bonding_curve: Pubkey,
-    #[serde_as(as = "DisplayFromStr")]
-    pub user: Pubkey,
-    pub created_at: String,
-}
-
-impl From<SubscribeUpdateTransaction> for TransactionPretty {
-    fn from(SubscribeUpdateTransaction { transaction, slot }: SubscribeUpdateTransaction) -> Self {
-        let tx = transaction.expect("should be defined");
-        Self {
+/// Fails on an update that isn't a well-formed transaction, as a corrupt
+/// recording or a bad queue message can carry.
+impl TryFrom<SubscribeUpdateTransaction> for TransactionPretty {
+    type Error = PumpfunError;
+
+    fn try_from(
+        SubscribeUpdateTransaction { transaction, slot }: SubscribeUpdateTransaction,
+    ) -> Result<Self, Self::Error> {
+        let invalid = |message: String| PumpfunError::InvalidUpdate(message);
+        let tx = transaction.ok_or_else(|| invalid("no transaction".to_string()))?;
+        let signature = Signature::try_from(tx.signature.as_slice()).map_err(|_| {
+            invalid(format!(
+                "signature of {} bytes, expected 64",
+                tx.signature.len()
+            ))
+        })?;
+        Ok(Self {
             slot,
-            signature: Signature::try_from(tx.signature.as_slice()).expect("valid signature"),
+            index: tx.index,
+            signature,
             is_vote: tx.is_vote,
             tx: yellowstone_grpc_proto::convert_from::create_tx_with_meta(tx)
-                .expect("valid tx with meta")
+                .map_err(|e| invalid(e.to_string()))?
                 .encode(UiTransactionEncoding::Base64, Some(u8::MAX), true)
-                .expect("failed to encode"),
-        }
+                .map_err(|e| invalid(e.to_string()))?,
+            provider_created_at: None,
+        })
     }
 }
 
-fn read_u32(data: &[u8]) -> u32 {
-    let mut bytes = [0u8; 4];
-    bytes.copy_from_slice(&data[..4]);
-    u32::from_le_bytes(bytes)
+impl TransactionPretty {
+    /// How many lamports `account`'s balance dropped by over the transaction,
+    /// negative when it grew. None if it isn't one of the transaction's accounts.
+    pub fn lamports_spent(&self, account: &Pubkey) -> Option<i64> {
+        let meta = self.tx.meta.as_ref()?;
+        let transaction = self.tx.transaction.decode()?;
+
+        // balances follow the static keys, then the lookup-table writable and readonly ones
+        let mut account_keys = transaction.message.static_account_keys().to_vec();
+        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+            for key in loaded.writable.iter().chain(&loaded.readonly) {
+                account_keys.push(key.parse().ok()?);
+            }
+        }
+        let index = account_keys.iter().position(|key| key == account)?;
+
+        let pre = *meta.pre_balances.get(index)? as i64;
+        let post = *meta.post_balances.get(index)? as i64;
+        Some(pre - post)
+    }
 }
 
-pub fn parse_create_token_data(data: &str) -> anyhow::Result<CreateTokenInfo> {
-    let decoded = base64
-        .decode(data)
-        .map_err(|e| anyhow::anyhow!("Failed to decode base64: {}", e))?;
+#[derive(Serialize, Deserialize)]
+struct OutputLogger<T> {
+    results: Vec<T>,
+}
 
-    // skip prefix bytes
-    let mut cursor = if decoded.len() > 8 { 8 } else { 0 };
+pub fn append_record(
+    path: &str,
+    format: OutputFormat,
+    token_info: &CreateTokenInfo,
+) -> anyhow::Result<()> {
+    append_records(path, format, std::slice::from_ref(token_info))
+}
 
-    // read name length and name
-    if cursor + 4 > decoded.len() {
-        return Err(anyhow::anyhow!("Data too short for name length"));
+/// Appends `token_infos` with a single write of the log.
+pub fn append_records(
+    path: &str,
+    format: OutputFormat,
+    token_infos: &[CreateTokenInfo],
+) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Json => append_all_to_json_file(path, token_infos),
+        OutputFormat::Borsh => append_all_to_borsh_file(path, token_infos),
     }
-    let name_len = read_u32(&decoded[cursor..]) as usize;
-    cursor += 4;
+}
 
-    if cursor + name_len > decoded.len() {
-        return Err(anyhow::anyhow!(
-            "Data too short for name: need {} bytes",
-            name_len
-        ));
-    }
-    let name = String::from_utf8(decoded[cursor..cursor + name_len].to_vec())
-        .map_err(|e| (anyhow::anyhow!("Invalid UTF-8 in name: {}", e)))?;
-    cursor += name_len;
+/// Every record in `path`, whichever format it was written in.
+pub fn read_records(path: &str, format: OutputFormat) -> anyhow::Result<Vec<CreateTokenInfo>> {
+    let file = File::open(path).map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path, e))?;
 
-    // read symbol length and symbol
-    if cursor + 4 > decoded.len() {
-        return Err(anyhow::anyhow!("Data too short for symbol length"));
+    match format {
+        OutputFormat::Json => {
+            let output_logger: OutputLogger<CreateTokenInfo> =
+                serde_json::from_reader(BufReader::new(file))
+                    .map_err(|e| anyhow::anyhow!("Invalid JSON log {}: {}", path, e))?;
+            Ok(output_logger.results)
+        }
+        OutputFormat::Borsh => BorshRecordReader::new(BufReader::new(file))
+            .collect::<Result<_, _>>()
+            .map_err(|e| anyhow::anyhow!("Invalid Borsh log {}: {}", path, e)),
     }
-    let symbol_len = read_u32(&decoded[cursor..]) as usize;
-    cursor += 4;
+}
 
-    if cursor + symbol_len > decoded.len() {
-        return Err(anyhow::anyhow!(
-            "Data too short for symbol: need {} bytes",
-            symbol_len
-        ));
-    }
-    let symbol = String::from_utf8(decoded[cursor..cursor + symbol_len].to_vec())
-        .map_err(|e| anyhow::anyhow!("Invalid UTF-8 in symbol: {}", e))?;
-    cursor += symbol_len;
+pub fn append_to_borsh_file(path: &str, token_info: &CreateTokenInfo) -> anyhow::Result<()> {
+    append_all_to_borsh_file(path, std::slice::from_ref(token_info))
+}
 
-    // read uri
-    if cursor + 4 > decoded.len() {
-        return Err(anyhow::anyhow!("Data too short for URI length",));
+pub fn append_all_to_borsh_file(path: &str, token_infos: &[CreateTokenInfo]) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path, e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| anyhow::anyhow!("Failed to stat {}: {}", path, e))?
+        .len();
+    // appending to a log of another version would leave it unreadable
+    if len > 0 {
+        File::open(path)
+            .and_then(|mut existing| read_borsh_header(&mut existing))
+            .map_err(|e| anyhow::anyhow!("Can't append to {}: {}", path, e))?;
+    }
+    // one write per batch so a crash can't leave a length without its body
+    let mut records = vec![];
+    if len == 0 {
+        write_borsh_header(&mut records)?;
+    }
+    for token_info in token_infos {
+        write_borsh_record(&mut records, token_info)?;
+    }
+    if let Err(e) = file.write_all(&records) {
+        // cut off a partly written batch so a retry appends after whole records
+        let _ = file.set_len(len);
+        return Err(anyhow::anyhow!("Failed to write {}: {}", path, e));
     }
-    let uri_len = read_u32(&decoded[cursor..]) as usize;
-    cursor += 4;
 
-    if cursor + uri_len > decoded.len() {
-        return Err(anyhow::anyhow!(
-            "Data too short for URI: need {} bytes",
-            uri_len
-        ));
+    Ok(())
+}
+
+/// Appends `record` to the `results` of the JSON log at `path`. Records are
+/// kept as plain JSON values, so full and projected records can share a file.
+pub fn append_to_json_file(path: &str, record: &impl Serialize) -> anyhow::Result<()> {
+    append_all_to_json_file(path, std::slice::from_ref(record))
+}
+
+// a name no earlier backup has, rename would silently replace one
+fn corrupt_backup_path(path: &str) -> String {
+    let backup = format!(
+        "{}.corrupt-{}",
+        path,
+        Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+    );
+    let mut candidate = backup.clone();
+    let mut n = 1;
+    while Path::new(&candidate).exists() {
+        candidate = format!("{}-{}", backup, n);
+        n += 1;
     }
-    let uri = String::from_utf8(decoded[cursor..cursor + uri_len].to_vec())
-        .map_err(|e| anyhow::anyhow!("Invalid UTF-8 in uri: {}", e))?;
-    cursor += uri_len;
+    candidate
+}
+
+/// `append_to_json_file` for several records, rewriting the log once.
+pub fn append_all_to_json_file(path: &str, records: &[impl Serialize]) -> anyhow::Result<()> {
+    append_all_to_json_file_with(path, records, true, false)
+}
+
+/// `append_all_to_json_file`, written indented when `pretty` or else compact.
+/// With `fsync` the new log is on disk before it replaces the old one, and
+/// the rename is synced after.
+pub fn append_all_to_json_file_with(
+    path: &str,
+    records: &[impl Serialize],
+    pretty: bool,
+    fsync: bool,
+) -> anyhow::Result<()> {
+    let mut output_logger: OutputLogger<serde_json::Value> = match fs::read(path) {
+        Ok(contents) => match serde_json::from_slice(&contents) {
+            Ok(output_logger) => output_logger,
+            Err(e) => {
+                let backup = corrupt_backup_path(path);
+                fs::rename(path, &backup).map_err(|rename_err| {
+                    anyhow::anyhow!(
+                        "{} is corrupt ({}) and could not be moved aside: {}",
+                        path,
+                        e,
+                        rename_err
+                    )
+                })?;
+                warn!(
+                    "{} is corrupt ({}), moved it to {} and started a new log",
+                    path, e, backup
+                );
+                OutputLogger { results: vec![] }
+            }
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => OutputLogger { results: vec![] },
+        Err(e) => return Err(anyhow::anyhow!("Failed to read {}: {}", path, e)),
+    };
 
-    // ? is enough data to read pubkeys ?
-    if cursor + 32 * 3 > decoded.len() {
-        return Err(anyhow::anyhow!("Data too short for public keys",));
+    for record in records {
+        output_logger.results.push(serde_json::to_value(record)?);
     }
 
-    // parse mint, bonding curve, user pubkeys
-    let mint = bs58::encode(&decoded[cursor..cursor + 32]).into_string();
-    cursor += 32;
-    let bonding_curve = bs58::encode(&decoded[cursor..cursor + 32]).into_string();
-    cursor += 32;
-    let user = bs58::encode(&decoded[cursor..cursor + 32]).into_string();
+    // written aside and renamed over, a failed write leaves the old log intact
+    let json = if pretty {
+        serde_json::to_string_pretty(&output_logger)?
+    } else {
+        serde_json::to_string(&output_logger)?
+    };
+    let staging = format!("{}.tmp", path);
+    let written = File::create(&staging).and_then(|mut file| {
+        file.write_all(json.as_bytes())?;
+        // else a crash after the rename can leave an empty log in its place
+        if fsync {
+            file.sync_all()?;
+        }
+        Ok(())
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&staging);
+        return Err(anyhow::anyhow!("Failed to write {}: {}", staging, e));
+    }
+    fs::rename(&staging, path).map_err(|e| anyhow::anyhow!("Failed to replace {}: {}", path, e))?;
+    if fsync {
+        sync_dir(path)?;
+    }
 
-    Ok(CreateTokenInfo {
-        name,
-        symbol,
-        uri,
-        mint: Pubkey::from_str(&mint).unwrap(),
-        bonding_curve: Pubkey::from_str(&bonding_curve).unwrap(),
-        user: Pubkey::from_str(&user).unwrap(),
-        created_at: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-    })
+    Ok(())
 }
 
-#[derive(Serialize, Deserialize)]
-struct ValidatorData {
-    identity_pubkey: String,
-    vote_account_pubkey: String,
-    credits: u64,
-    delta: u64,
+/// Flushes `path` and the directory entry pointing at it to disk, so a write
+/// already made survives a power loss.
+pub fn sync_file(path: &str) -> anyhow::Result<()> {
+    File::open(path)
+        .and_then(|file| file.sync_all())
+        .map_err(|e| anyhow::anyhow!("Failed to sync {}: {}", path, e))?;
+    sync_dir(path)
 }
 
-#[derive(Serialize, Deserialize)]
-struct ResultEntry {
-    validators: Vec<ValidatorData>,
-    epoch: u64,
-    timestamp: String,
-}
+// a newly created or renamed file is only durable once its directory is synced
+fn sync_dir(path: &str) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        let dir = match std::path::Path::new(path).parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => std::path::Path::new("."),
+        };
+        File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(|e| anyhow::anyhow!("Failed to sync {}: {}", dir.display(), e))?;
+    }
 
-#[derive(Serialize, Deserialize)]
-struct OutputLogger {
-    results: Vec<CreateTokenInfo>,
+    Ok(())
 }
 
-pub fn append_to_json_file(token_info: &CreateTokenInfo) -> anyhow::Result<()> {
-    let mut output_logger = match File::open("create_token_log.json") {
-        Ok(mut file) => {
-            let mut contents = String::new();
-            file.read_to_string(&mut contents).unwrap();
-            serde_json::from_str(&contents).unwrap_or(OutputLogger { results: vec![] })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch(symbol: &str) -> CreateTokenInfo {
+        CreateTokenInfo {
+            symbol: symbol.to_string(),
+            ..Default::default()
         }
-        Err(_) => OutputLogger { results: vec![] },
-    };
+    }
+
+    #[test]
+    fn appends_to_an_existing_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.json");
+        let path = path.to_str().unwrap();
 
-    output_logger.results.push(token_info.clone());
+        append_to_json_file(path, &launch("ONE")).unwrap();
+        append_all_to_json_file(path, &[launch("TWO"), launch("THREE")]).unwrap();
 
-    let json = serde_json::to_string_pretty(&output_logger).unwrap();
-    let mut file = File::create("create_token_log.json").unwrap();
-    file.write_all(json.as_bytes()).unwrap();
+        let symbols: Vec<_> = read_records(path, OutputFormat::Json)
+            .unwrap()
+            .into_iter()
+            .map(|token_info| token_info.symbol)
+            .collect();
+        assert_eq!(symbols, ["ONE", "TWO", "THREE"]);
+    }
 
-    println!("Results logged to create_token_log.json");
+    // kept for inspection under a `.corrupt-` name, the log starts over
+    #[test]
+    fn moves_a_corrupt_log_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.json");
+        fs::write(&path, "{\"results\": [").unwrap();
+        let path = path.to_str().unwrap();
 
-    Ok(())
+        append_to_json_file(path, &launch("NEW")).unwrap();
+
+        let records = read_records(path, OutputFormat::Json).unwrap();
+        assert_eq!(records, [launch("NEW")]);
+        let backups: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|entry| entry.to_string_lossy().contains("log.json.corrupt-"))
+            .collect();
+        assert_eq!(backups.len(), 1, "{:?}", backups);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "{\"results\": [");
+    }
+
+    // two within the same instant keep both backups
+    #[test]
+    fn keeps_every_corrupt_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.json");
+        let path = path.to_str().unwrap();
+
+        for corrupt in ["{\"results\": [", "not json"] {
+            fs::write(path, corrupt).unwrap();
+            append_to_json_file(path, &launch("NEW")).unwrap();
+        }
+        let backup = corrupt_backup_path(path);
+        fs::write(&backup, "").unwrap();
+        assert_ne!(corrupt_backup_path(path), backup);
+
+        let mut backups: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|entry| entry.to_string_lossy().contains("log.json.corrupt-"))
+            .map(|entry| fs::read_to_string(entry).unwrap())
+            .collect();
+        backups.sort();
+        assert_eq!(backups, ["", "not json", "{\"results\": ["]);
+    }
 }
@@ -0,0 +1,46 @@
+//! On-chain sanity check of log-derived mints, to catch parser misalignment
+//! that would otherwise emit plausible-looking garbage pubkeys.
+
+use std::time::Duration;
+
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::trade::TOKEN_PROGRAM_ID;
+
+// a just-processed mint can be a slot or two ahead of the RPC node
+const ATTEMPTS: usize = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+pub struct MintVerifier {
+    rpc: RpcClient,
+}
+
+impl MintVerifier {
+    pub fn new(rpc_url: &str) -> Self {
+        Self {
+            rpc: RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::processed()),
+        }
+    }
+
+    /// Whether `mint` exists and is owned by the SPL token program.
+    pub async fn verify(&self, mint: &Pubkey) -> anyhow::Result<bool> {
+        for attempt in 1..=ATTEMPTS {
+            let account = self
+                .rpc
+                .get_account_with_commitment(mint, CommitmentConfig::processed())
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to get account {}: {}", mint, e))?
+                .value;
+
+            match account {
+                Some(account) => return Ok(account.owner == TOKEN_PROGRAM_ID),
+                None if attempt < ATTEMPTS => tokio::time::sleep(RETRY_DELAY).await,
+                None => {}
+            }
+        }
+
+        Ok(false)
+    }
+}
@@ -0,0 +1,99 @@
+//! Wallet freshness from the RPC's signature history: new wallets funded just
+//! to launch are a common sign of a coordinated launch.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::Semaphore;
+
+// a serial launcher's wallet comes up again and again, so results are kept a while
+const CACHE_TTL: Duration = Duration::from_secs(600);
+const MAX_CACHED: usize = 10_000;
+// one page of `getSignaturesForAddress`
+const MAX_SIGNATURES: usize = 1_000;
+
+#[derive(Clone)]
+pub struct FreshWalletChecker {
+    rpc: Arc<RpcClient>,
+    permits: Arc<Semaphore>,
+    max_txs: usize,
+    max_age: Option<Duration>,
+    cache: Arc<Mutex<HashMap<Pubkey, (std::time::Instant, bool)>>>,
+}
+
+impl FreshWalletChecker {
+    /// A wallet is fresh with fewer than `max_txs` transactions, and also, if
+    /// `max_age` is set, a first transaction no older than it.
+    pub fn new(
+        rpc_url: &str,
+        max_txs: usize,
+        max_age: Option<Duration>,
+        concurrency: usize,
+    ) -> Self {
+        Self {
+            rpc: Arc::new(RpcClient::new_with_commitment(
+                rpc_url.to_string(),
+                CommitmentConfig::confirmed(),
+            )),
+            permits: Arc::new(Semaphore::new(concurrency.max(1))),
+            max_txs: max_txs.clamp(1, MAX_SIGNATURES),
+            max_age,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn is_fresh(&self, wallet: &Pubkey) -> anyhow::Result<bool> {
+        if let Some(fresh) = self.cached(wallet) {
+            return Ok(fresh);
+        }
+        let _permit = self.permits.acquire().await?;
+        // another launch by the same wallet may have looked it up meanwhile
+        if let Some(fresh) = self.cached(wallet) {
+            return Ok(fresh);
+        }
+
+        let signatures = self
+            .rpc
+            .get_signatures_for_address_with_config(
+                wallet,
+                GetConfirmedSignaturesForAddress2Config {
+                    limit: Some(self.max_txs),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get signatures of {}: {}", wallet, e))?;
+
+        // newest first, so a short page ends with the wallet's first transaction
+        let fresh = signatures.len() < self.max_txs
+            && self.max_age.is_none_or(|max_age| {
+                signatures
+                    .last()
+                    .and_then(|first| first.block_time)
+                    .is_none_or(|first_seen| {
+                        let age = chrono::Utc::now().timestamp() - first_seen;
+                        age <= max_age.as_secs() as i64
+                    })
+            });
+
+        let mut cache = self.cache.lock().expect("cache lock poisoned");
+        if cache.len() >= MAX_CACHED {
+            cache.retain(|_, (checked_at, _)| checked_at.elapsed() < CACHE_TTL);
+        }
+        cache.insert(*wallet, (std::time::Instant::now(), fresh));
+        Ok(fresh)
+    }
+
+    fn cached(&self, wallet: &Pubkey) -> Option<bool> {
+        let cache = self.cache.lock().expect("cache lock poisoned");
+        cache
+            .get(wallet)
+            .filter(|(checked_at, _)| checked_at.elapsed() < CACHE_TTL)
+            .map(|(_, fresh)| *fresh)
+    }
+}
@@ -0,0 +1,41 @@
+use solana_pumpfun::curve::{BONDING_CURVE_DISCRIMINATOR, BondingCurveState};
+
+fn account_data(fields: &[u64], complete: bool) -> Vec<u8> {
+    let mut data = BONDING_CURVE_DISCRIMINATOR.to_vec();
+    for field in fields {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.push(complete as u8);
+    data
+}
+
+#[test]
+fn decodes_bonding_curve_account() {
+    let mut data = account_data(
+        &[
+            1_000_000_000_000_000,
+            32_000_000_000,
+            720_000_000_000_000,
+            2_000_000_000,
+            1_000_000_000_000_000,
+        ],
+        false,
+    );
+    // newer accounts append the creator
+    data.extend_from_slice(&[7; 32]);
+
+    let state = BondingCurveState::from_account_data(&data).unwrap();
+    assert_eq!(state.virtual_sol_reserves, 32_000_000_000);
+    assert_eq!(state.real_token_reserves, 720_000_000_000_000);
+    assert!(!state.complete);
+    assert!((state.price_sol() - 0.000_000_032).abs() < 1e-15);
+    assert!((state.real_sol() - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn rejects_other_accounts() {
+    let mut data = account_data(&[0; 5], true);
+    data[0] ^= 1;
+    assert!(BondingCurveState::from_account_data(&data).is_err());
+    assert!(BondingCurveState::from_account_data(&data[..4]).is_err());
+}
@@ -0,0 +1,87 @@
+use futures_util::{StreamExt, stream};
+use solana_pumpfun::parser::PumpfunEvent;
+use solana_pumpfun::stream::follow_mint;
+use solana_sdk::pubkey::Pubkey;
+use yellowstone_grpc_proto::prelude::subscribe_update::UpdateOneof;
+use yellowstone_grpc_proto::prelude::{
+    SubscribeUpdate, SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
+    TransactionStatusMeta,
+};
+
+fn update(log: &str) -> SubscribeUpdate {
+    let meta = TransactionStatusMeta {
+        log_messages: log.lines().map(str::to_string).collect(),
+        ..Default::default()
+    };
+    SubscribeUpdate {
+        update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                meta: Some(meta),
+                ..Default::default()
+            }),
+            slot: 1,
+        })),
+        ..Default::default()
+    }
+}
+
+// the MCAT create with its dev buy, a later buy, then its migration and a swap on the pool
+fn updates() -> Vec<SubscribeUpdate> {
+    vec![
+        include_str!("fixtures/nested_self_cpi.log"),
+        include_str!("fixtures/direct_buy.log"),
+        include_str!("fixtures/amm_migration.log"),
+    ]
+    .into_iter()
+    .map(update)
+    .collect()
+}
+
+fn mint() -> Pubkey {
+    "FqUwnBMN1shpeqKVm7W5fN73tvrjVr19TQFFgkoFFzhq"
+        .parse()
+        .unwrap()
+}
+
+// the swap names only the pool, which the migration tied to the mint
+#[tokio::test]
+async fn follows_a_mint_from_create_to_its_pool() {
+    let mint = mint();
+    let events: Vec<_> = follow_mint(stream::iter(updates().into_iter().map(Ok)), mint)
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 5, "got {:?}", events);
+    assert!(matches!(&events[0], PumpfunEvent::Create(token_info) if token_info.mint == mint));
+    assert!(matches!(&events[1], PumpfunEvent::Trade(trade_info) if trade_info.mint == mint));
+    assert!(matches!(&events[2], PumpfunEvent::Trade(trade_info) if trade_info.mint == mint));
+    let PumpfunEvent::AmmCreatePool(pool_info) = &events[3] else {
+        panic!("expected the pool, got {:?}", events[3]);
+    };
+    assert_eq!(pool_info.base_mint, mint);
+    assert!(
+        matches!(&events[4], PumpfunEvent::AmmSwap(swap_info) if swap_info.pool == pool_info.pool)
+    );
+}
+
+#[tokio::test]
+async fn leaves_out_other_mints() {
+    let events: Vec<_> = follow_mint(
+        stream::iter(updates().into_iter().map(Ok)),
+        Pubkey::new_unique(),
+    )
+    .collect()
+    .await;
+
+    assert!(events.is_empty(), "got {:?}", events);
+}
+
+#[tokio::test]
+async fn ends_at_the_first_error() {
+    let mut updates: Vec<_> = updates().into_iter().map(Ok).collect();
+    updates.insert(1, Err(tonic::Status::unavailable("gone")));
+    let events: Vec<_> = follow_mint(stream::iter(updates), mint()).collect().await;
+
+    // the create and dev buy from before it
+    assert_eq!(events.len(), 2, "got {:?}", events);
+}
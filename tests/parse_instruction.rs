@@ -0,0 +1,363 @@
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as base64;
+use solana_pubkey::Pubkey;
+use solana_pumpfun::error::PumpfunError;
+use solana_pumpfun::parser::{
+    CREATE_EVENT_DISCRIMINATOR, CreateTokenInfo, PUMPFUN_PROGRAM_ID, PUMPSWAP_PROGRAM_ID,
+    ParseOptions, PumpfunEvent, parse_create_token_data, parse_create_token_data_with,
+    parse_events, parse_instruction, parse_instruction_names, parse_invocations_with,
+    parse_program_invocations_with,
+};
+
+fn fixture(log: &str) -> Vec<String> {
+    log.lines().map(str::to_string).collect()
+}
+
+#[test]
+fn parses_direct_create() {
+    let logs = fixture(include_str!("fixtures/direct_create.log"));
+    let instructions = parse_instruction(&logs).unwrap();
+
+    assert_eq!(instructions.len(), 1);
+    let token_info = &instructions[0];
+    assert_eq!(token_info.name, "Moon Cat");
+    assert_eq!(token_info.symbol, "MCAT");
+    assert_eq!(token_info.uri, "https://ipfs.io/ipfs/QmMoonCat");
+    assert_eq!(
+        token_info.mint.to_string(),
+        "FqUwnBMN1shpeqKVm7W5fN73tvrjVr19TQFFgkoFFzhq"
+    );
+    assert_eq!(
+        token_info.bonding_curve.to_string(),
+        "9fbt64APipnCQGKyY5PjZYfKw4HkAnESEmupLZhuUiwG"
+    );
+    assert_eq!(
+        token_info.user.to_string(),
+        "LQVcTQajEfHFgC7dJeWJ6R3uBsqZrSdp9rTzv344p4A"
+    );
+    assert_eq!(token_info.invoke_count, 1);
+    assert_eq!(token_info.max_invoke_depth, 1);
+    // this layout ends at the user
+    assert_eq!(token_info.token_supply, None);
+    assert_eq!(token_info.decimals(), 6);
+}
+
+// the same create with the current event's tail appended: creator,
+// timestamp, reserves and total supply
+#[test]
+fn parses_supply_from_extended_create() {
+    let logs = fixture(include_str!("fixtures/extended_create.log"));
+    let instructions = parse_instruction(&logs).unwrap();
+
+    assert_eq!(instructions.len(), 1);
+    let token_info = &instructions[0];
+    assert_eq!(token_info.symbol, "MCAT");
+    assert_eq!(
+        token_info.user.to_string(),
+        "LQVcTQajEfHFgC7dJeWJ6R3uBsqZrSdp9rTzv344p4A"
+    );
+    assert_eq!(token_info.token_supply, Some(1_000_000_000_000_000));
+    assert_eq!(token_info.block_time, Some(1_718_900_000));
+    // not in the event
+    assert_eq!(token_info.decimals, None);
+    assert_eq!(token_info.decimals(), 6);
+    assert!(!token_info.partial);
+
+    let legacy = CreateTokenInfo::default();
+    assert_eq!(token_info.initial_price_sol(), legacy.initial_price_sol());
+    assert_eq!(
+        token_info.initial_market_cap_sol(),
+        legacy.initial_market_cap_sol()
+    );
+}
+
+// a tail cut off before the supply leaves it unset rather than misread
+#[test]
+fn ignores_an_incomplete_create_tail() {
+    let logs = fixture(include_str!("fixtures/extended_create.log"));
+    let data = logs
+        .iter()
+        .find_map(|line| line.strip_prefix("Program data: "))
+        .unwrap();
+    let mut decoded = base64.decode(data).unwrap();
+    decoded.truncate(decoded.len() - 8);
+
+    let token_info = parse_create_token_data(&base64.encode(decoded)).unwrap();
+    assert_eq!(
+        token_info.user.to_string(),
+        "LQVcTQajEfHFgC7dJeWJ6R3uBsqZrSdp9rTzv344p4A"
+    );
+    assert_eq!(token_info.token_supply, None);
+    assert_eq!(token_info.block_time, None);
+    assert_eq!(token_info.supply(), 1_000_000_000_000_000);
+}
+
+fn create_data(log: &str) -> String {
+    fixture(log)
+        .iter()
+        .find_map(|line| line.strip_prefix("Program data: "))
+        .unwrap()
+        .to_string()
+}
+
+fn allow_partial() -> ParseOptions {
+    ParseOptions {
+        allow_partial: true,
+        ..ParseOptions::default()
+    }
+}
+
+// discriminator, "Moon Cat" and "MCAT" and nothing after
+#[test]
+fn keeps_a_create_cut_off_after_the_symbol_with_allow_partial() {
+    let mut decoded = base64
+        .decode(create_data(include_str!("fixtures/direct_create.log")))
+        .unwrap();
+    decoded.truncate(8 + 4 + 8 + 4 + 4);
+    let data = base64.encode(decoded);
+
+    let token_info = parse_create_token_data_with(&data, &allow_partial()).unwrap();
+    assert!(token_info.partial);
+    assert_eq!(token_info.name, "Moon Cat");
+    assert_eq!(token_info.symbol, "MCAT");
+    assert_eq!(token_info.uri, "");
+    assert_eq!(token_info.mint, Pubkey::default());
+
+    let err = parse_create_token_data(&data).unwrap_err();
+    assert!(
+        matches!(&err, PumpfunError::Truncated { field, .. } if field == "uri length"),
+        "got {:?}",
+        err
+    );
+}
+
+// a line cut mid base64 group is invalid base64 unless partial records are allowed
+#[test]
+fn keeps_a_create_cut_off_mid_base64_group_with_allow_partial() {
+    let data = create_data(include_str!("fixtures/direct_create.log"));
+    let data = &data[..50];
+
+    let token_info = parse_create_token_data_with(data, &allow_partial()).unwrap();
+    assert!(token_info.partial);
+    assert_eq!(token_info.symbol, "MCAT");
+    assert_eq!(token_info.mint, Pubkey::default());
+
+    let err = parse_create_token_data(data).unwrap_err();
+    assert!(matches!(err, PumpfunError::Decode(_)), "got {:?}", err);
+}
+
+// a name that's really there, but longer than any launch's
+#[test]
+fn rejects_oversized_string_fields() {
+    let mut data = CREATE_EVENT_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&5_000u32.to_le_bytes());
+    data.extend_from_slice(&[b'a'; 5_000]);
+    let data = base64.encode(data);
+
+    let err = parse_create_token_data(&data).unwrap_err();
+    assert!(
+        matches!(&err, PumpfunError::FieldTooLong { field, len: 5_000, max: 4_096 } if field == "name"),
+        "got {:?}",
+        err
+    );
+
+    // allowed through, it then runs out of data at the symbol
+    let options = ParseOptions {
+        max_field_len: 10_000,
+        ..ParseOptions::default()
+    };
+    let err = parse_create_token_data_with(&data, &options).unwrap_err();
+    assert!(
+        matches!(err, PumpfunError::Truncated { .. }),
+        "got {:?}",
+        err
+    );
+}
+
+#[test]
+fn ignores_pumpfun_reached_through_router_cpi() {
+    let logs = fixture(include_str!("fixtures/jupiter_routed_swap.log"));
+    let instructions = parse_instruction(&logs).unwrap();
+
+    assert!(instructions.is_empty());
+}
+
+// the router's buy is still a trade, only creates have to be top level
+#[test]
+fn parses_a_buy_reached_through_router_cpi() {
+    let logs = fixture(include_str!("fixtures/jupiter_routed_swap.log"));
+    let events = parse_events(&logs).unwrap();
+
+    assert_eq!(events.len(), 1, "got {:?}", events);
+    let PumpfunEvent::Trade(trade_info) = &events[0] else {
+        panic!("expected a trade, got {:?}", events[0]);
+    };
+    assert!(trade_info.is_buy);
+    assert_eq!(trade_info.sol_amount, 500_000_000);
+}
+
+// Pumpfun -> token program -> Pumpfun, plus a Pumpfun self-CPI, then a top-level buy
+#[test]
+fn tracks_depth_through_nested_pumpfun_cpis() {
+    let logs = fixture(include_str!("fixtures/nested_self_cpi.log"));
+    let invocations = parse_invocations_with(&logs, &ParseOptions::default()).unwrap();
+
+    assert_eq!(invocations.len(), 2);
+    let PumpfunEvent::Create(token_info) = &invocations[0].event else {
+        panic!("expected a create, got {:?}", invocations[0].event);
+    };
+    assert_eq!(token_info.symbol, "MCAT");
+    assert_eq!(token_info.invoke_count, 4);
+    assert_eq!(token_info.max_invoke_depth, 2);
+    assert_eq!(invocations[0].logs, 2..17);
+    // the buy is only seen if the depth got back to zero after the create
+    assert!(matches!(invocations[1].event, PumpfunEvent::Trade(_)));
+    assert_eq!(invocations[1].logs, 17..logs.len());
+}
+
+// the same fixture's top-level buy is the creator's, bundled with the create
+#[test]
+fn attaches_bundled_dev_buy() {
+    let logs = fixture(include_str!("fixtures/nested_self_cpi.log"));
+    let instructions = parse_instruction(&logs).unwrap();
+
+    assert_eq!(instructions.len(), 1);
+    assert_eq!(instructions[0].dev_buy_sol, Some(0.5));
+    assert_eq!(instructions[0].dev_buy_tokens, Some(17_000_000_000_000));
+
+    let logs = fixture(include_str!("fixtures/direct_create.log"));
+    let instructions = parse_instruction(&logs).unwrap();
+    assert_eq!(instructions[0].dev_buy_sol, None);
+    assert_eq!(instructions[0].dev_buy_tokens, None);
+}
+
+// Anchor event-CPI: the create event is logged by Pumpfun's self-invoke, next
+// to a longer `Program data:` of the instruction's own that isn't one
+#[test]
+fn prefers_the_event_cpi_payload() {
+    let logs = fixture(include_str!("fixtures/event_cpi_create.log"));
+    let invocations = parse_invocations_with(&logs, &ParseOptions::default()).unwrap();
+
+    assert_eq!(invocations.len(), 1, "got {:?}", invocations);
+    let PumpfunEvent::Create(token_info) = &invocations[0].event else {
+        panic!("expected a create, got {:?}", invocations[0].event);
+    };
+    assert_eq!(token_info.symbol, "MCAT");
+    assert_eq!(
+        invocations[0].program_data,
+        logs[10]["Program data: ".len()..]
+    );
+}
+
+// a program logging what looks like an invoke mustn't open a frame
+#[test]
+fn ignores_invoke_lines_inside_program_logs() {
+    let mut logs = fixture(include_str!("fixtures/direct_create.log"));
+    let data = logs
+        .iter()
+        .position(|line| line.starts_with("Program data: "))
+        .unwrap();
+    logs.insert(
+        data,
+        "Program log: Program 11111111111111111111111111111111 invoke [1]".to_string(),
+    );
+
+    let instructions = parse_instruction(&logs).unwrap();
+    assert_eq!(instructions.len(), 1);
+    assert_eq!(instructions[0].max_invoke_depth, 1);
+}
+
+// a provider dropped the inner self-CPI's success line; the next invoke's
+// bracketed depth shows that frame is over
+#[test]
+fn recovers_from_a_missing_inner_success_line() {
+    let logs = fixture(include_str!("fixtures/missing_inner_success.log"));
+    let events = parse_events(&logs).unwrap();
+
+    assert_eq!(events.len(), 2, "got {:?}", events);
+    assert!(matches!(&events[0], PumpfunEvent::Create(token_info) if token_info.symbol == "MCAT"));
+    assert!(matches!(events[1], PumpfunEvent::Trade(_)));
+}
+
+// names come from Pumpfun's own frames only, not the token program or AMM it calls
+#[test]
+fn lists_pumpfun_instruction_names() {
+    let logs = fixture(include_str!("fixtures/nested_self_cpi.log"));
+    assert_eq!(parse_instruction_names(&logs), ["Create", "Buy"]);
+
+    let logs = fixture(include_str!("fixtures/admin_instructions.log"));
+    assert_eq!(parse_instruction_names(&logs), ["ExtendAccount", "Migrate"]);
+}
+
+// a create event logged inside an admin instruction's CPI (here the AMM's
+// `CreatePool` during `Migrate`) used to be taken for a launch
+#[test]
+fn ignores_data_under_admin_instructions() {
+    let logs = fixture(include_str!("fixtures/admin_instructions.log"));
+    let events = parse_events(&logs).unwrap();
+
+    assert!(events.is_empty(), "expected no events, got {:?}", events);
+}
+
+// the pool created inside Pumpfun's `Migrate`, then a top-level AMM buy
+#[test]
+fn parses_amm_migration_and_swap() {
+    let logs = fixture(include_str!("fixtures/amm_migration.log"));
+    let programs = [PUMPFUN_PROGRAM_ID, PUMPSWAP_PROGRAM_ID];
+    let invocations =
+        parse_program_invocations_with(&logs, &programs, &ParseOptions::default()).unwrap();
+
+    assert_eq!(invocations.len(), 2, "got {:?}", invocations);
+    let PumpfunEvent::AmmCreatePool(pool_info) = &invocations[0].event else {
+        panic!("expected a pool, got {:?}", invocations[0].event);
+    };
+    assert_eq!(
+        pool_info.base_mint.to_string(),
+        "FqUwnBMN1shpeqKVm7W5fN73tvrjVr19TQFFgkoFFzhq"
+    );
+    assert_eq!(
+        pool_info.pool.to_string(),
+        "9fbt64APipnCQGKyY5PjZYfKw4HkAnESEmupLZhuUiwG"
+    );
+    assert_eq!(pool_info.quote_amount_in, 84_990_359_056);
+    assert_eq!(invocations[0].logs, 4..12);
+
+    let PumpfunEvent::AmmSwap(swap_info) = &invocations[1].event else {
+        panic!("expected a swap, got {:?}", invocations[1].event);
+    };
+    assert!(swap_info.is_buy);
+    assert_eq!(swap_info.pool, pool_info.pool);
+    assert_eq!(swap_info.base_amount, 1_000_000_000);
+    assert_eq!(swap_info.quote_amount, 410_000_000);
+    assert_eq!(
+        swap_info.user.to_string(),
+        "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM"
+    );
+
+    // without pump-swap selected it's only an admin instruction
+    let pumpfun_only =
+        parse_program_invocations_with(&logs, &[PUMPFUN_PROGRAM_ID], &ParseOptions::default())
+            .unwrap();
+    assert!(pumpfun_only.is_empty(), "got {:?}", pumpfun_only);
+}
+
+#[test]
+fn parses_direct_buy_trade() {
+    let logs = fixture(include_str!("fixtures/direct_buy.log"));
+    let events = parse_events(&logs).unwrap();
+
+    assert_eq!(events.len(), 1);
+    let PumpfunEvent::Trade(trade_info) = &events[0] else {
+        panic!("expected a trade, got {:?}", events[0]);
+    };
+    assert_eq!(
+        trade_info.mint.to_string(),
+        "FqUwnBMN1shpeqKVm7W5fN73tvrjVr19TQFFgkoFFzhq"
+    );
+    assert!(trade_info.is_buy);
+    assert_eq!(trade_info.sol_amount, 500_000_000);
+    assert_eq!(trade_info.token_amount, 17_000_000_000_000);
+    assert_eq!(trade_info.timestamp, 1_760_000_000);
+    assert_eq!(trade_info.virtual_sol_reserves, 30_500_000_000);
+    assert_eq!(trade_info.virtual_token_reserves, 1_056_000_000_000_000);
+}
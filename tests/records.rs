@@ -0,0 +1,73 @@
+use solana_pumpfun::parser::parse_instruction;
+use solana_pumpfun::records::{
+    BorshRecordReader, MAGIC, MAX_RECORD_LEN, VERSION, write_borsh_header, write_borsh_record,
+};
+
+#[test]
+fn borsh_records_round_trip() {
+    let logs: Vec<String> = include_str!("fixtures/direct_create.log")
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let mut token_info = parse_instruction(&logs).unwrap().remove(0);
+    token_info.detection_latency_ms = Some(412);
+
+    let mut buf = vec![];
+    write_borsh_header(&mut buf).unwrap();
+    write_borsh_record(&mut buf, &token_info).unwrap();
+    write_borsh_record(&mut buf, &token_info).unwrap();
+
+    let records = BorshRecordReader::new(buf.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(records, vec![token_info.clone(), token_info]);
+}
+
+#[test]
+fn empty_input_has_no_records() {
+    assert_eq!(BorshRecordReader::new(&[][..]).count(), 0);
+}
+
+// a log from before the header, or another format entirely
+#[test]
+fn rejects_a_log_without_the_header() {
+    let mut buf = vec![];
+    write_borsh_record(&mut buf, &Default::default()).unwrap();
+
+    let err = BorshRecordReader::new(buf.as_slice())
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert!(err.to_string().contains("no record log header"), "{}", err);
+}
+
+#[test]
+fn rejects_another_version() {
+    let mut buf = MAGIC.to_vec();
+    buf.extend_from_slice(&(VERSION + 1).to_le_bytes());
+
+    let err = BorshRecordReader::new(buf.as_slice())
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains(&format!("version {}", VERSION + 1)),
+        "{}",
+        err
+    );
+}
+
+// a corrupt length fails before anything that size is allocated
+#[test]
+fn rejects_an_oversized_length() {
+    let mut buf = vec![];
+    write_borsh_header(&mut buf).unwrap();
+    buf.extend_from_slice(&(MAX_RECORD_LEN as u32 + 1).to_le_bytes());
+
+    let err = BorshRecordReader::new(buf.as_slice())
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}